@@ -1 +1,8 @@
+pub mod answer;
+pub mod differential;
 pub mod file_io;
+pub mod log;
+pub mod maths;
+pub mod parse;
+pub mod progress;
+pub mod rng;
@@ -0,0 +1,227 @@
+use std::{any::type_name, convert::Infallible, fmt, str::FromStr};
+
+/// What a part comes out with. Numbers are kept at full width so no day has to pick a type
+/// that's big enough for everyone.
+#[derive(Debug, Clone)]
+pub enum Answer {
+    Signed(i128),
+    Unsigned(u128),
+    Text(String),
+}
+
+/// What a part returns: its answer, or why it couldn't get one.
+pub type Solved = Result<Answer, Unsolved>;
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Signed(value) => write!(f, "{value}"),
+            Answer::Unsigned(value) => write!(f, "{value}"),
+            Answer::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+/// Numbers are equal by value whether they were worked out signed or not.
+impl PartialEq for Answer {
+    fn eq(&self, other: &Answer) -> bool {
+        match (self, other) {
+            (Answer::Signed(a), Answer::Signed(b)) => a == b,
+            (Answer::Unsigned(a), Answer::Unsigned(b)) => a == b,
+            (Answer::Signed(signed), Answer::Unsigned(unsigned))
+            | (Answer::Unsigned(unsigned), Answer::Signed(signed)) => {
+                u128::try_from(*signed).is_ok_and(|signed| signed == *unsigned)
+            }
+            (Answer::Text(a), Answer::Text(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Answer {}
+
+/// Read an answer as written in an answers file. Anything that isn't a number is text.
+impl FromStr for Answer {
+    type Err = Infallible;
+
+    fn from_str(text: &str) -> Result<Answer, Infallible> {
+        let text = text.trim();
+        Ok(if let Ok(value) = text.parse() {
+            Answer::Unsigned(value)
+        } else if let Ok(value) = text.parse() {
+            Answer::Signed(value)
+        } else {
+            Answer::Text(String::from(text))
+        })
+    }
+}
+
+macro_rules! answer_from {
+    ($variant:ident as $wide:ty: $($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Answer {
+                    Answer::$variant(value as $wide)
+                }
+            }
+        )*
+    };
+}
+
+answer_from!(Unsigned as u128: u32, u64, u128, usize);
+answer_from!(Signed as i128: i32, i64, i128);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Answer {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Answer {
+        Answer::Text(String::from(text))
+    }
+}
+
+/// A sum or product that didn't fit in the type it was worked out in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overflow(pub &'static str);
+
+impl Overflow {
+    pub fn of<T>() -> Overflow {
+        Overflow(type_name::<T>())
+    }
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "overflowed {}", self.0)
+    }
+}
+
+/// Why a part came out without an answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Unsolved {
+    Overflow(Overflow),
+    // the input parsed but there's nothing to answer, like a network with no way to the end
+    NoAnswer(String),
+    // the input didn't parse, with the parser's report
+    Malformed(String),
+}
+
+impl fmt::Display for Unsolved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unsolved::Overflow(overflow) => write!(f, "{overflow}"),
+            Unsolved::NoAnswer(why) => write!(f, "no answer, {why}"),
+            Unsolved::Malformed(error) => write!(f, "malformed input, {error}"),
+        }
+    }
+}
+
+impl From<Overflow> for Unsolved {
+    fn from(overflow: Overflow) -> Unsolved {
+        Unsolved::Overflow(overflow)
+    }
+}
+
+/// The integer types `checked_sum` and `checked_product` work over.
+pub trait Checked: Copy {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! checked {
+    ($($t:ty),*) => {
+        $(
+            impl Checked for $t {
+                const ZERO: $t = 0;
+                const ONE: $t = 1;
+
+                fn checked_add(self, other: $t) -> Option<$t> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn checked_mul(self, other: $t) -> Option<$t> {
+                    <$t>::checked_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+checked!(u32, u64, u128, usize, i32, i64, i128);
+
+/// Add `values` up, stopping with an error rather than wrapping if the total doesn't fit.
+pub fn checked_sum<T: Checked>(values: impl IntoIterator<Item = T>) -> Result<T, Overflow> {
+    values.into_iter().try_fold(T::ZERO, |total, value| {
+        total.checked_add(value).ok_or_else(Overflow::of::<T>)
+    })
+}
+
+/// Multiply `values` together, stopping with an error rather than wrapping if it doesn't fit.
+pub fn checked_product<T: Checked>(values: impl IntoIterator<Item = T>) -> Result<T, Overflow> {
+    values.into_iter().try_fold(T::ONE, |total, value| {
+        total.checked_mul(value).ok_or_else(Overflow::of::<T>)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checked_product, checked_sum, Answer, Overflow};
+
+    #[test]
+    fn test_parse_and_display() {
+        let answers: Vec<Answer> = [
+            "288",
+            "-7",
+            "340282366920938463463374607431768211455",
+            "AAA",
+        ]
+        .iter()
+        .map(|text| text.parse().unwrap())
+        .collect();
+
+        assert_eq!(Answer::Unsigned(288), answers[0]);
+        assert_eq!(Answer::Signed(-7), answers[1]);
+        assert_eq!(Answer::Unsigned(u128::MAX), answers[2]);
+        assert_eq!(Answer::Text(String::from("AAA")), answers[3]);
+
+        let shown: Vec<String> = answers.iter().map(Answer::to_string).collect();
+        assert_eq!(
+            vec![
+                "288",
+                "-7",
+                "340282366920938463463374607431768211455",
+                "AAA"
+            ],
+            shown
+        );
+    }
+
+    #[test]
+    fn test_equal_by_value() {
+        assert_eq!(Answer::from(46_i64), Answer::from(46_u32));
+        assert_eq!(Answer::from(-1_i32), Answer::Signed(-1));
+        assert_ne!(Answer::from(-1_i32), Answer::from(u128::MAX));
+        assert_ne!(Answer::from(5_u32), Answer::from("5"));
+    }
+
+    #[test]
+    fn test_checked_sum() {
+        assert_eq!(Ok(6), checked_sum([1_u32, 2, 3]));
+        assert_eq!(Ok(0), checked_sum(Vec::<i64>::new()));
+        assert_eq!(Err(Overflow("u32")), checked_sum([u32::MAX, 1]));
+        assert_eq!(Err(Overflow("i32")), checked_sum([i32::MIN, -1]));
+        assert_eq!("overflowed u32", Overflow::of::<u32>().to_string());
+    }
+
+    #[test]
+    fn test_checked_product() {
+        assert_eq!(Ok(288), checked_product([4_u64, 8, 9]));
+        assert_eq!(Err(Overflow("u64")), checked_product([u64::MAX, 2]));
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt::Debug;
+
+use crate::common::{maths::fnv1a, rng::Rng};
+
+/// A randomly generated test case that knows how to make smaller versions of itself.
+pub trait Shrink: Clone + Debug {
+    /// Candidates that are each a little simpler than `self`, simplest first.
+    fn shrink(&self) -> Vec<Self>;
+}
+
+/// Run `cases` random cases from `generate` through `agree`, which returns a description of how
+/// the two implementations differ or `Ok` if they match. On the first disagreement the case is
+/// shrunk for as long as a smaller version still fails, then the test panics with that case.
+pub fn check<C, G, A>(name: &str, cases: usize, generate: G, agree: A)
+where
+    C: Shrink,
+    G: Fn(&mut Rng) -> C,
+    A: Fn(&C) -> Result<(), String>,
+{
+    let mut rng = Rng::new(seed_for(name));
+
+    for _ in 0..cases {
+        let case = generate(&mut rng);
+        if let Err(difference) = agree(&case) {
+            let (case, difference) = minimise(case, difference, &agree);
+            panic!("{name}: implementations disagree on {case:#?}\n{difference}");
+        }
+    }
+}
+
+fn minimise<C, A>(mut case: C, mut difference: String, agree: &A) -> (C, String)
+where
+    C: Shrink,
+    A: Fn(&C) -> Result<(), String>,
+{
+    'smaller: loop {
+        for candidate in case.shrink() {
+            if let Err(candidate_difference) = agree(&candidate) {
+                case = candidate;
+                difference = candidate_difference;
+                continue 'smaller;
+            }
+        }
+        return (case, difference);
+    }
+}
+
+/// Each check gets its own fixed seed so failures reproduce from run to run.
+fn seed_for(name: &str) -> u64 {
+    fnv1a(name.as_bytes())
+}
+
+/// Copies of `items` with one element removed, for cases built from lists.
+pub fn without_each<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    (0..items.len())
+        .map(|skip| {
+            let mut fewer = items.to_vec();
+            fewer.remove(skip);
+            fewer
+        })
+        .collect()
+}
+
+/// Smaller numbers to try in place of `n`, heading towards `floor`.
+pub fn smaller(n: u64, floor: u64) -> Vec<u64> {
+    let mut candidates = Vec::new();
+    if n > floor {
+        candidates.push(floor);
+        let half = floor + (n - floor) / 2;
+        if half != floor {
+            candidates.push(half);
+        }
+        if n - 1 != half && n - 1 != floor {
+            candidates.push(n - 1);
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, smaller, without_each, Shrink};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Numbers(Vec<u64>);
+
+    impl Shrink for Numbers {
+        fn shrink(&self) -> Vec<Numbers> {
+            let mut candidates: Vec<Numbers> =
+                without_each(&self.0).into_iter().map(Numbers).collect();
+            for (index, n) in self.0.iter().enumerate() {
+                for smaller in smaller(*n, 0) {
+                    let mut numbers = self.0.clone();
+                    numbers[index] = smaller;
+                    candidates.push(Numbers(numbers));
+                }
+            }
+            candidates
+        }
+    }
+
+    #[test]
+    fn test_agreeing_implementations_pass() {
+        check(
+            "sum",
+            100,
+            |rng| Numbers((0..rng.below(10)).map(|_| rng.below(1000)).collect()),
+            |case| {
+                let mut looped = 0;
+                for n in &case.0 {
+                    looped += n;
+                }
+                let summed: u64 = case.0.iter().sum();
+                if looped == summed {
+                    Ok(())
+                } else {
+                    Err(format!("{looped} != {summed}"))
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Numbers(\n    [\n        10,\n    ],\n)")]
+    fn test_failures_are_shrunk() {
+        // a "fast" sum that goes wrong once any single number reaches 10
+        check(
+            "buggy sum",
+            100,
+            |rng| Numbers((0..5).map(|_| rng.below(1000)).collect()),
+            |case| {
+                let buggy: u64 = case.0.iter().map(|n| n.min(&9)).sum();
+                let right: u64 = case.0.iter().sum();
+                if buggy == right {
+                    Ok(())
+                } else {
+                    Err(format!("{buggy} != {right}"))
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_smaller() {
+        assert_eq!(vec![0, 50, 99], smaller(100, 0));
+        assert_eq!(vec![1], smaller(2, 1));
+        assert!(smaller(1, 1).is_empty());
+    }
+}
@@ -3,9 +3,12 @@ use std::fs;
 pub fn read_file_to_vec(file_path: &str) -> Vec<String> {
     let contents = fs::read_to_string(file_path).expect("error reading file");
 
-    let lines: Vec<String> = contents.split('\n').map(|s| String::from(s)).collect();
+    split_lines(&contents)
+}
 
-    lines
+/// Split an input already in memory the same way `read_file_to_vec` splits a file.
+pub fn split_lines(input: &str) -> Vec<String> {
+    input.split('\n').map(String::from).collect()
 }
 
 pub fn read_file_to_string(file_path: &str) -> String {
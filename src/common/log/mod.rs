@@ -0,0 +1,180 @@
+use std::{fmt, sync::OnceLock};
+
+/// How much detail a message carries, least detail first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn parse(name: &str) -> Option<Level> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Which messages get through: a default level plus overrides for individual modules, e.g.
+/// `debug,day9=trace` shows debug everywhere and trace in day 9.
+#[derive(Debug, PartialEq)]
+pub struct Filter {
+    default: Level,
+    modules: Vec<(String, Level)>,
+}
+
+impl Default for Filter {
+    /// Errors, warnings and info only, so debug output doesn't slow down timed runs.
+    fn default() -> Filter {
+        Filter {
+            default: Level::Info,
+            modules: Vec::new(),
+        }
+    }
+}
+
+impl Filter {
+    pub fn parse(spec: &str) -> Result<Filter, String> {
+        let mut filter = Filter::default();
+
+        for directive in spec.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let level = |name: &str| {
+                Level::parse(name).ok_or_else(|| format!("{name:?} is not a log level"))
+            };
+            match directive.split_once('=') {
+                Some((module, name)) => filter.modules.push((String::from(module), level(name)?)),
+                None => filter.default = level(directive)?,
+            }
+        }
+
+        Ok(filter)
+    }
+
+    /// The level for the module a message came from. `module` is a `module_path!()` such as
+    /// `aoc23_1::day9`, matched on the part after the crate name.
+    pub fn level_for(&self, module: &str) -> Level {
+        let module = module.split_once("::").map_or(module, |(_, rest)| rest);
+        self.modules
+            .iter()
+            .rev()
+            .find(|(name, _)| module == name || module.starts_with(&format!("{name}::")))
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    pub fn enabled(&self, level: Level, module: &str) -> bool {
+        level <= self.level_for(module)
+    }
+}
+
+static FILTER: OnceLock<Filter> = OnceLock::new();
+
+/// Set the filter for the rest of the run. Only the first call has any effect.
+pub fn init(filter: Filter) {
+    let _ = FILTER.set(filter);
+}
+
+pub fn enabled(level: Level, module: &str) -> bool {
+    FILTER.get_or_init(Filter::default).enabled(level, module)
+}
+
+/// Write a message to stderr, tagged with its level and module, if the filter lets it through.
+/// Use the `error!` to `trace!` macros rather than calling this directly so arguments aren't
+/// formatted for messages nobody will see.
+pub fn write(level: Level, module: &str, message: fmt::Arguments) {
+    let module = module.split_once("::").map_or(module, |(_, rest)| rest);
+    eprintln!("[{level} {module}] {message}");
+}
+
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::common::log::enabled($level, module_path!()) {
+            $crate::common::log::write($level, module_path!(), format_args!($($arg)+));
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log!($crate::common::log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::log!($crate::common::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log!($crate::common::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::common::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::common::log::Level::Trace, $($arg)+) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Filter, Level};
+
+    #[test]
+    fn test_default_hides_debug() {
+        let filter = Filter::default();
+
+        assert!(filter.enabled(Level::Info, "aoc23_1::day5"));
+        assert!(!filter.enabled(Level::Debug, "aoc23_1::day5"));
+    }
+
+    #[test]
+    fn test_per_module_levels() {
+        let filter = Filter::parse("warn,day9=trace,common=debug").unwrap();
+
+        assert_eq!(Level::Trace, filter.level_for("aoc23_1::day9"));
+        assert_eq!(Level::Warn, filter.level_for("aoc23_1::day10"));
+        assert_eq!(Level::Warn, filter.level_for("aoc23_1::day90"));
+        assert_eq!(Level::Debug, filter.level_for("aoc23_1::common::rng"));
+        assert!(filter.enabled(Level::Trace, "aoc23_1::day9::tests"));
+        assert!(!filter.enabled(Level::Info, "aoc23_1::day5"));
+    }
+
+    #[test]
+    fn test_later_directives_win() {
+        let filter = Filter::parse("day5=trace,day5=error").unwrap();
+        assert_eq!(Level::Error, filter.level_for("aoc23_1::day5"));
+    }
+
+    #[test]
+    fn test_bad_level() {
+        assert_eq!(
+            Err(String::from("\"loud\" is not a log level")),
+            Filter::parse("day9=loud")
+        );
+    }
+}
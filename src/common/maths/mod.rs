@@ -0,0 +1,25 @@
+/// 64 bit FNV-1a, a small stable hash for telling inputs apart.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100_0000_01b3)
+    })
+}
+
+pub fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Lowest common multiple, the first step at which things repeating every `a` and every `b`
+/// steps line up.
+pub fn lcm(a: u64, b: u64) -> u64 {
+    a / gcd(a, b) * b
+}
+
+/// `lcm`, or `None` if it doesn't fit in a `u64`.
+pub fn checked_lcm(a: u64, b: u64) -> Option<u64> {
+    (a / gcd(a, b)).checked_mul(b)
+}
@@ -0,0 +1,360 @@
+use std::{any::type_name, fmt, str::FromStr};
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alphanumeric1, char, digit1, line_ending, multispace0, space0},
+    combinator::{eof, not, opt, peek, recognize},
+    error::{context, ContextError, ErrorKind, FromExternalError},
+    multi::many0,
+    sequence::{pair, preceded, terminated},
+    IResult,
+};
+
+/// What a parser needed to see at the point it failed, pointing into the input.
+#[derive(Debug, PartialEq)]
+pub struct Expected<'a> {
+    at: &'a str,
+    what: String,
+}
+
+impl<'a> Expected<'a> {
+    pub fn new(at: &'a str, what: impl Into<String>) -> Expected<'a> {
+        Expected {
+            at,
+            what: what.into(),
+        }
+    }
+}
+
+impl<'a> nom::error::ParseError<&'a str> for Expected<'a> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Expected<'a> {
+        Expected::new(input, describe(kind))
+    }
+
+    // the innermost failure says the most about what was wrong
+    fn append(_: &'a str, _: ErrorKind, other: Expected<'a>) -> Expected<'a> {
+        other
+    }
+
+    fn from_char(input: &'a str, c: char) -> Expected<'a> {
+        Expected::new(input, format!("{c:?}"))
+    }
+
+    /// Of two alternatives keep whichever got further into the input.
+    fn or(self, other: Expected<'a>) -> Expected<'a> {
+        if other.at.len() < self.at.len() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<'a> ContextError<&'a str> for Expected<'a> {
+    /// A context names what was being parsed, which is more use than the inner failure unless
+    /// the inner parser got some way in before failing.
+    fn add_context(input: &'a str, context: &'static str, other: Expected<'a>) -> Expected<'a> {
+        if other.at.len() == input.len() {
+            Expected::new(input, context)
+        } else {
+            other
+        }
+    }
+}
+
+impl<'a, E> FromExternalError<&'a str, E> for Expected<'a> {
+    fn from_external_error(input: &'a str, kind: ErrorKind, _: E) -> Expected<'a> {
+        Expected::new(input, describe(kind))
+    }
+}
+
+fn describe(kind: ErrorKind) -> String {
+    let what = match kind {
+        ErrorKind::Digit => "a number",
+        ErrorKind::Alpha => "a letter",
+        ErrorKind::AlphaNumeric => "a name",
+        ErrorKind::Space | ErrorKind::MultiSpace => "a space",
+        ErrorKind::CrLf => "end of line",
+        ErrorKind::Eof => "end of input",
+        kind => return String::from(kind.description()),
+    };
+    String::from(what)
+}
+
+pub type PResult<'a, O> = IResult<&'a str, O, Expected<'a>>;
+
+/// A failed parse, with the line it failed on and a caret under the column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    // 1 based and counted in characters
+    pub column: usize,
+    pub text: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    /// Locate `expected` within the whole `input` it was parsed from.
+    pub fn new(input: &str, expected: Expected) -> ParseError {
+        let offset = input.len() - expected.at.len();
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            text: String::from(input[line_start..].lines().next().unwrap_or("")),
+            expected: expected.what,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "line {}, column {}: expected {}",
+            self.line, self.column, self.expected
+        )?;
+        writeln!(f, "    {}", self.text)?;
+        write!(f, "    {}^", " ".repeat(self.column - 1))
+    }
+}
+
+/// Run `parser` over the whole of `input`. Anything left over other than trailing whitespace is
+/// an error.
+pub fn parse_all<'a, O>(
+    input: &'a str,
+    parser: impl FnMut(&'a str) -> PResult<'a, O>,
+) -> Result<O, ParseError> {
+    match terminated(parser, pair(multispace0, eof))(input) {
+        Ok((_, output)) => Ok(output),
+        Err(nom::Err::Error(expected)) | Err(nom::Err::Failure(expected)) => {
+            Err(ParseError::new(input, expected))
+        }
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::new(
+            input,
+            Expected::new(&input[input.len()..], "more input"),
+        )),
+    }
+}
+
+/// Exactly `text`, reported by its contents when it's missing.
+pub fn literal<'a>(text: &'a str) -> impl Fn(&'a str) -> PResult<'a, &'a str> {
+    move |input| {
+        tag(text)(input)
+            .map_err(|error| error.map(|_: Expected| Expected::new(input, format!("{text:?}"))))
+    }
+}
+
+/// A whole number, optionally negative, that fits in `T`.
+pub fn number<T: FromStr>(input: &str) -> PResult<'_, T> {
+    let (rest, digits) = recognize(pair(opt(char('-')), digit1))(input)
+        .map_err(|error| error.map(|_: Expected| Expected::new(input, "a number")))?;
+
+    match digits.parse() {
+        Ok(value) => Ok((rest, value)),
+        Err(_) => Err(nom::Err::Failure(Expected::new(
+            input,
+            format!("a number that fits in {}", type_name::<T>()),
+        ))),
+    }
+}
+
+/// One or more numbers separated by spaces or tabs, such as `79 14 55 13`. Trailing spaces are
+/// consumed too.
+pub fn numbers<T: FromStr>(input: &str) -> PResult<'_, Vec<T>> {
+    let (mut rest, first) = number(input)?;
+    let mut values = vec![first];
+
+    loop {
+        let (after, spaces) = space0(rest)?;
+        match number(after) {
+            Ok((after, value)) if !spaces.is_empty() => {
+                values.push(value);
+                rest = after;
+            }
+            Err(nom::Err::Failure(failure)) => return Err(nom::Err::Failure(failure)),
+            _ => return Ok((after, values)),
+        }
+    }
+}
+
+/// A `Label: values` line, such as `Time:      7  15   30`.
+pub fn labelled<'a, O>(
+    label: &'a str,
+    values: impl FnMut(&'a str) -> PResult<'a, O>,
+) -> impl FnMut(&'a str) -> PResult<'a, O> {
+    preceded(
+        terminated(literal(label), pair(literal(":"), space0)),
+        values,
+    )
+}
+
+/// Letters and digits naming something, such as a node or a colour.
+pub fn name(input: &str) -> PResult<'_, &str> {
+    alphanumeric1(input)
+}
+
+/// A `KEY = (A, B)` line, giving the key and the pair.
+pub fn node(input: &str) -> PResult<'_, (&str, (&str, &str))> {
+    let (input, key) = terminated(name, space0)(input)?;
+    let (input, _) = terminated(literal("="), space0)(input)?;
+    let (input, _) = literal("(")(input)?;
+    let (input, left) = terminated(name, pair(literal(","), space0))(input)?;
+    let (input, right) = terminated(name, literal(")"))(input)?;
+
+    Ok((input, (key, (left, right))))
+}
+
+/// `item`s separated by `separator`. Once a separator has matched the next item must follow,
+/// so a bad item is reported where it is rather than ending the list early, unless there's
+/// nothing but whitespace after the separator.
+pub fn list<'a, S, O>(
+    mut separator: impl FnMut(&'a str) -> PResult<'a, S>,
+    mut item: impl FnMut(&'a str) -> PResult<'a, O>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<O>> {
+    move |input| {
+        let (mut rest, first) = item(input)?;
+        let mut items = vec![first];
+
+        loop {
+            let after = match separator(rest) {
+                Ok((after, _)) if !after.trim().is_empty() => after,
+                Err(nom::Err::Failure(failure)) => return Err(nom::Err::Failure(failure)),
+                _ => return Ok((rest, items)),
+            };
+            let (after, next) = item(after)?;
+            items.push(next);
+            rest = after;
+        }
+    }
+}
+
+/// One `line` per line, up to a blank line or the end of the input. Each `line` has to take
+/// everything up to the end of its line.
+pub fn lines<'a, O>(
+    line: impl FnMut(&'a str) -> PResult<'a, O>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<O>> {
+    let line = terminated(line, context("end of line", peek(alt((line_ending, eof)))));
+    list(terminated(line_ending, not(line_ending)), line)
+}
+
+/// One or more empty lines between two blocks.
+pub fn blank_line(input: &str) -> PResult<'_, ()> {
+    let (input, _) = pair(line_ending, pair(space0, line_ending))(input)
+        .map_err(|error| error.map(|_: Expected| Expected::new(input, "a blank line")))?;
+    let (input, _) = many0(alt((line_ending, terminated(space0, line_ending))))(input)?;
+    Ok((input, ()))
+}
+
+/// `block`s separated by blank lines, such as the maps in an almanac.
+pub fn blocks<'a, O>(
+    block: impl FnMut(&'a str) -> PResult<'a, O>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<O>> {
+    list(blank_line, block)
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::{
+        character::complete::line_ending,
+        sequence::{pair, separated_pair, terminated},
+    };
+
+    use super::{
+        blocks, labelled, lines, list, literal, name, node, number, numbers, parse_all, ParseError,
+    };
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(
+            Ok(vec![79, 14, -55, 13]),
+            parse_all("79  14\t-55 13  ", numbers::<i64>)
+        );
+        assert_eq!(
+            Ok(vec![7, 15, 30]),
+            parse_all("Time:      7  15   30", labelled("Time", numbers::<u32>))
+        );
+    }
+
+    #[test]
+    fn test_number_too_big() {
+        let error = parse_all("1 2 300", numbers::<u8>).unwrap_err();
+
+        assert_eq!((1, 5), (error.line, error.column));
+        assert_eq!("a number that fits in u8", error.expected);
+    }
+
+    #[test]
+    fn test_node() {
+        assert_eq!(
+            Ok(("AAA", ("BBB", "CCC"))),
+            parse_all("AAA = (BBB, CCC)", node)
+        );
+        assert_eq!(
+            Ok(vec![("11A", ("11B", "XXX")), ("11B", ("XXX", "11Z"))]),
+            parse_all("11A = (11B, XXX)\n11B = (XXX, 11Z)\n", lines(node))
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        let block = |input| {
+            let (input, label) = terminated(name, pair(literal(":"), line_ending))(input)?;
+            let (input, rows) = lines(numbers::<u32>)(input)?;
+            Ok((input, (label, rows)))
+        };
+
+        assert_eq!(
+            Ok(vec![
+                ("a", vec![vec![1, 2], vec![3, 4]]),
+                ("b", vec![vec![5]])
+            ]),
+            parse_all("a:\n1 2\n3 4\n\n \nb:\n5\n", blocks(block))
+        );
+    }
+
+    #[test]
+    fn test_error_points_at_bad_line() {
+        let error = parse_all("1 2\n3 4\n5 x\n", lines(numbers::<u32>)).unwrap_err();
+
+        assert_eq!(
+            ParseError {
+                line: 3,
+                column: 3,
+                text: String::from("5 x"),
+                expected: String::from("end of line"),
+            },
+            error
+        );
+        assert_eq!(
+            "line 3, column 3: expected end of line\n    5 x\n      ^",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_error_after_separator() {
+        let pull = separated_pair(number::<u32>, literal(" "), name);
+        let error = parse_all("1 blue, 2 green, lots red", list(literal(", "), pull)).unwrap_err();
+
+        assert_eq!((1, 18), (error.line, error.column));
+        assert_eq!("a number", error.expected);
+    }
+
+    #[test]
+    fn test_missing_label() {
+        let error = parse_all("Time: 1\nDistence: 2", |input| {
+            let (input, time) = labelled("Time", number::<u32>)(input)?;
+            let (input, _) = line_ending(input)?;
+            let (input, distance) = labelled("Distance", number::<u32>)(input)?;
+            Ok((input, (time, distance)))
+        })
+        .unwrap_err();
+
+        assert_eq!((2, 1), (error.line, error.column));
+        assert_eq!("\"Distance\"", error.expected);
+    }
+}
@@ -0,0 +1,257 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::info;
+
+// the clock and the cancel flag are only looked at once every this many steps
+const CHECK_EVERY: u64 = 4096;
+const REPORT_EVERY: Duration = Duration::from_secs(5);
+
+static DEFAULT_TIMEOUT: OnceLock<Duration> = OnceLock::new();
+
+thread_local! {
+    // the cancel flag every search started on this thread shares, once one is installed
+    static INSTALLED: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// Give every search started from now on this long before it stops, as set by `--timeout`.
+/// Only the first call has any effect.
+pub fn set_default_timeout(timeout: Duration) {
+    let _ = DEFAULT_TIMEOUT.set(timeout);
+}
+
+/// The timeout set by `--timeout`, if there was one.
+pub fn default_timeout() -> Option<Duration> {
+    DEFAULT_TIMEOUT.get().copied()
+}
+
+/// Why a search gave up before finding its answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stopped {
+    TimedOut { steps: u64 },
+    Cancelled { steps: u64 },
+}
+
+impl fmt::Display for Stopped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stopped::TimedOut { steps } => write!(f, "timed out after {steps} steps"),
+            Stopped::Cancelled { steps } => write!(f, "cancelled after {steps} steps"),
+        }
+    }
+}
+
+/// Stops the search it came from at its next check, from any thread.
+#[derive(Clone)]
+pub struct Canceller {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Canceller {
+    /// A canceller not tied to any search yet, see `install`.
+    pub fn new() -> Canceller {
+        Canceller {
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Every `Progress` made on this thread from now on stops when this is cancelled, so code
+    /// that runs a solver can stop the searches inside it without handing them a `Progress`.
+    pub fn install(&self) {
+        INSTALLED.with(|installed| *installed.borrow_mut() = Some(Arc::clone(&self.cancelled)));
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Default for Canceller {
+    fn default() -> Canceller {
+        Canceller::new()
+    }
+}
+
+/// Passed into a long search, which calls `step` as it goes. Every so often it logs how far the
+/// search has got, and it ends the search once the deadline passes or it's cancelled.
+pub struct Progress {
+    label: String,
+    // how many steps the search could take at most, when it's known
+    total: Option<u64>,
+    steps: u64,
+    next_check: u64,
+    started: Instant,
+    deadline: Option<Instant>,
+    last_report: Instant,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Progress {
+    /// A handle with the `--timeout` deadline, if there was one, reporting every five seconds.
+    /// It's cancelled along with the thread's installed `Canceller`, if it has one.
+    pub fn new(label: &str) -> Progress {
+        let now = Instant::now();
+        Progress {
+            label: String::from(label),
+            total: None,
+            steps: 0,
+            next_check: CHECK_EVERY,
+            started: now,
+            deadline: DEFAULT_TIMEOUT.get().map(|timeout| now + *timeout),
+            last_report: now,
+            cancelled: INSTALLED
+                .with(|installed| installed.borrow().clone())
+                .unwrap_or_else(|| Arc::new(AtomicBool::new(false))),
+        }
+    }
+
+    /// The most steps the search can take, so reports can say when it'll be done. Usually only
+    /// the search itself knows this.
+    pub fn set_total(&mut self, total: u64) {
+        self.total = Some(total);
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Progress {
+        self.deadline = Some(self.started + timeout);
+        self
+    }
+
+    pub fn canceller(&self) -> Canceller {
+        Canceller {
+            cancelled: Arc::clone(&self.cancelled),
+        }
+    }
+
+    pub fn step(&mut self) -> Result<(), Stopped> {
+        self.advance(1)
+    }
+
+    /// Count `steps` more steps, stopping the search if its time is up or it's been cancelled.
+    pub fn advance(&mut self, steps: u64) -> Result<(), Stopped> {
+        self.steps += steps;
+        if self.steps < self.next_check {
+            return Ok(());
+        }
+        self.next_check = self.steps + CHECK_EVERY;
+
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(Stopped::Cancelled { steps: self.steps });
+        }
+
+        let now = Instant::now();
+        if self.deadline.is_some_and(|deadline| now >= deadline) {
+            return Err(Stopped::TimedOut { steps: self.steps });
+        }
+        if now - self.last_report >= REPORT_EVERY {
+            self.last_report = now;
+            info!("{}", self.summary(now - self.started));
+        }
+
+        Ok(())
+    }
+
+    /// Steps so far and the rate, plus how much is left when the total is known.
+    pub fn summary(&self, elapsed: Duration) -> String {
+        let seconds = elapsed.as_secs_f64();
+        let rate = if seconds > 0.0 {
+            self.steps as f64 / seconds
+        } else {
+            0.0
+        };
+        let mut summary = format!(
+            "{}: {} steps in {seconds:.1}s, {rate:.0} steps/s",
+            self.label, self.steps
+        );
+
+        if let Some(total) = self.total.filter(|total| *total > 0) {
+            let done = self.steps.min(total) as f64 / total as f64;
+            summary.push_str(&format!(", {:.1}% done", done * 100.0));
+            if rate > 0.0 {
+                let left = (total.saturating_sub(self.steps)) as f64 / rate;
+                summary.push_str(&format!(", about {left:.0}s left"));
+            }
+        }
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::{Canceller, Progress, Stopped, CHECK_EVERY};
+
+    #[test]
+    fn test_times_out() {
+        let mut progress = Progress::new("test").with_timeout(Duration::ZERO);
+
+        let stopped = loop {
+            if let Err(stopped) = progress.step() {
+                break stopped;
+            }
+        };
+
+        assert_eq!(Stopped::TimedOut { steps: CHECK_EVERY }, stopped);
+        assert_eq!("timed out after 4096 steps", stopped.to_string());
+    }
+
+    #[test]
+    fn test_cancel_from_another_handle() {
+        let mut progress = Progress::new("test");
+        let canceller = progress.canceller();
+
+        assert_eq!(Ok(()), progress.advance(10));
+        canceller.cancel();
+        assert_eq!(
+            Err(Stopped::Cancelled {
+                steps: CHECK_EVERY + 10
+            }),
+            progress.advance(CHECK_EVERY)
+        );
+    }
+
+    #[test]
+    fn test_installed_canceller() {
+        let canceller = Canceller::new();
+        let installed = canceller.clone();
+        let stopped = thread::spawn(move || {
+            installed.install();
+            let mut progress = Progress::new("test");
+            installed.cancel();
+            progress.advance(CHECK_EVERY)
+        })
+        .join()
+        .unwrap();
+        assert_eq!(Err(Stopped::Cancelled { steps: CHECK_EVERY }), stopped);
+
+        // only the thread it was installed on is affected
+        canceller.cancel();
+        assert_eq!(Ok(()), Progress::new("test").advance(CHECK_EVERY));
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut progress = Progress::new("search");
+        progress.set_total(1000);
+        progress.advance(250).unwrap();
+
+        assert_eq!(
+            "search: 250 steps in 2.0s, 125 steps/s, 25.0% done, about 6s left",
+            progress.summary(Duration::from_secs(2))
+        );
+
+        let progress = Progress::new("search");
+        assert_eq!(
+            "search: 0 steps in 0.0s, 0 steps/s",
+            progress.summary(Duration::ZERO)
+        );
+    }
+}
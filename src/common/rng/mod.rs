@@ -0,0 +1,70 @@
+/// Small seeded random number generator (splitmix64) so generated inputs can be reproduced from
+/// their seed. Not suitable for anything that needs real randomness.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`. `bound` must not be zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// A number in `low..=high`.
+    pub fn between(&mut self, low: u64, high: u64) -> u64 {
+        low + self.below(high - low + 1)
+    }
+
+    /// True `percent` times out of a hundred.
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rng;
+
+    #[test]
+    fn test_repeatable() {
+        let first: Vec<u64> = (0..5)
+            .scan(Rng::new(42), |rng, _| Some(rng.next_u64()))
+            .collect();
+        let second: Vec<u64> = (0..5)
+            .scan(Rng::new(42), |rng, _| Some(rng.next_u64()))
+            .collect();
+        assert_eq!(first, second);
+        assert_ne!(first[0], Rng::new(43).next_u64());
+    }
+
+    #[test]
+    fn test_between_is_inclusive() {
+        let mut rng = Rng::new(1);
+        let values: Vec<u64> = (0..200).map(|_| rng.between(3, 5)).collect();
+        assert!(values.iter().all(|v| (3..=5).contains(v)));
+        assert!(values.contains(&3) && values.contains(&5));
+    }
+}
@@ -1,88 +1,325 @@
-use crate::common::file_io;
-use regex::Regex;
+use std::{convert::Infallible, fs};
 
-pub fn puz2() {
-    let lines: Vec<String> = file_io::read_file_to_vec("input/day1-input");
+use aho_corasick::{AhoCorasick, MatchKind};
 
-    let mut calibration_value: u32 = 0;
-
-    for line in lines {
-        let line = &line;
-        // find first match
-        let matcher =
-            Regex::new("[0-9]|(one)|(two)|(three)|(four)|(five)|(six)|(seven)|(eight)|(nine)");
-        let reverse_matcher =
-            Regex::new("[0-9]|(eno)|(owt)|(eerht)|(ruof)|(evif)|(xis)|(neves)|(thgie)|(enin)");
-        let forward_re = matcher.unwrap();
-        let backward_re = reverse_matcher.unwrap();
-
-        let first = forward_re
-            .find(&line)
-            .expect("failed to find first")
-            .as_str();
-        let first = token_to_number(first);
-
-        let reverse_line: String = line.chars().rev().collect();
-        let last: String = backward_re
-            .find(&reverse_line)
-            .expect("failed to find last")
-            .as_str()
-            .chars()
-            .rev()
-            .collect();
-        let last = token_to_number(last.as_str());
+use crate::{
+    common::{
+        answer::{checked_sum, Answer, Overflow, Solved},
+        file_io,
+    },
+    runner::Solution,
+};
 
-        let assembled_number = String::new() + &first.to_string() + &last.to_string();
-        let number: u32 = assembled_number.parse().expect("Failed to parse number");
+const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
 
-        calibration_value = calibration_value + number;
+const ENGLISH: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+const ROMAN: [&str; 9] = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX"];
+
+/// The spellings a [`DigitScanner`] recognises, each paired with the digit it stands for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vocabulary {
+    tokens: Vec<(String, u32)>,
+}
+
+impl Vocabulary {
+    /// The characters 0 to 9, as used by part 1.
+    pub fn digits() -> Vocabulary {
+        Vocabulary::spelled(&DIGITS, 0)
     }
 
-    println!("The calibration value is : {calibration_value}");
+    /// Digits plus the English words one to nine, as used by part 2.
+    pub fn english() -> Vocabulary {
+        Vocabulary::digits().with(Vocabulary::spelled(&ENGLISH, 1))
+    }
+
+    /// The word "zero", which the puzzle itself never uses.
+    pub fn zero() -> Vocabulary {
+        Vocabulary::spelled(&["zero"], 0)
+    }
+
+    /// Upper case Roman numerals I to IX.
+    pub fn roman() -> Vocabulary {
+        Vocabulary::spelled(&ROMAN, 1)
+    }
+
+    /// Words for consecutive digits starting at `first`, e.g. `spelled(&["eins", "zwei"], 1)`.
+    pub fn spelled(words: &[&str], first: u32) -> Vocabulary {
+        Vocabulary {
+            tokens: (first..)
+                .zip(words)
+                .map(|(value, word)| (String::from(*word), value))
+                .collect(),
+        }
+    }
+
+    /// Read a vocabulary with one `<word> <digit>` pair per line. Blank lines and lines starting
+    /// with `#` are skipped.
+    pub fn parse(input: &str) -> Result<Vocabulary, String> {
+        let mut tokens = Vec::new();
+
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (word, value) = line
+                .rsplit_once(char::is_whitespace)
+                .ok_or_else(|| format!("line {}: expected \"<word> <digit>\"", index + 1))?;
+            let value: u32 = value
+                .parse()
+                .ok()
+                .filter(|value| *value <= 9)
+                .ok_or_else(|| format!("line {}: {value:?} is not a digit", index + 1))?;
+
+            tokens.push((String::from(word.trim()), value));
+        }
+
+        if tokens.is_empty() {
+            return Err(String::from("vocabulary has no words"));
+        }
+
+        Ok(Vocabulary { tokens })
+    }
+
+    pub fn from_file(file_path: &str) -> Result<Vocabulary, String> {
+        let contents = fs::read_to_string(file_path).map_err(|e| format!("{file_path}: {e}"))?;
+        Vocabulary::parse(&contents)
+    }
+
+    /// Combine two vocabularies into one.
+    pub fn with(mut self, other: Vocabulary) -> Vocabulary {
+        self.tokens.extend(other.tokens);
+        self
+    }
 }
 
-fn token_to_number(token: &str) -> &str {
-    let var_name = match token {
-        "one" => "1",
-        "two" => "2",
-        "three" => "3",
-        "four" => "4",
-        "five" => "5",
-        "six" => "6",
-        "seven" => "7",
-        "eight" => "8",
-        "nine" => "9",
-        c => c,
-    };
-    return var_name;
+#[derive(Debug, PartialEq)]
+pub struct DigitToken {
+    // byte offset of the first character of the token in the line
+    position: usize,
+    length: usize,
+    value: u32,
+}
+
+impl DigitToken {
+    /// The digit the token stands for.
+    pub fn digit(&self) -> u32 {
+        self.value
+    }
+
+    /// Byte offset of the token's first character in the line.
+    pub fn start(&self) -> usize {
+        self.position
+    }
+
+    /// Byte offset just past the token's last character, so `&line[token.start()..token.end()]`
+    /// is the token.
+    pub fn end(&self) -> usize {
+        self.position + self.length
+    }
+}
+
+/// Finds every digit token in a line in one pass, including tokens that share letters such as
+/// the "two" and "one" in "twone".
+pub struct DigitScanner {
+    matcher: AhoCorasick,
+    values: Vec<u32>,
+}
+
+impl DigitScanner {
+    pub fn new(vocabulary: &Vocabulary) -> DigitScanner {
+        let matcher = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .build(vocabulary.tokens.iter().map(|(token, _)| token))
+            .expect("failed to build digit scanner");
+
+        DigitScanner {
+            matcher,
+            values: vocabulary.tokens.iter().map(|(_, value)| *value).collect(),
+        }
+    }
+
+    /// Every token in the line ordered by where it starts.
+    pub fn tokens(&self, line: &str) -> Vec<DigitToken> {
+        let mut tokens: Vec<DigitToken> = self
+            .matcher
+            .find_overlapping_iter(line)
+            .map(|m| DigitToken {
+                position: m.start(),
+                length: m.len(),
+                value: self.values[m.pattern().as_usize()],
+            })
+            .collect();
+        tokens.sort_by_key(|token| token.position);
+        tokens
+    }
+
+    /// First and last digit of the line combined into a two digit number. When tokens nest, as
+    /// "VIII" holds "V" and "III", the longest one wins.
+    pub fn calibration_value(&self, line: &str) -> Option<u32> {
+        let tokens = self.tokens(line);
+        let first = tokens
+            .iter()
+            .min_by_key(|token| (token.position, usize::MAX - token.length))?;
+        let last = tokens
+            .iter()
+            .max_by_key(|token| (token.position + token.length, token.length))?;
+        Some(first.value * 10 + last.value)
+    }
+}
+
+/// Sum the calibration values of every line, skipping lines with no digits in them. A total too
+/// big for a `u32` is reported rather than wrapped.
+pub fn calibrate(lines: &[String], vocabulary: &Vocabulary) -> Result<u32, Overflow> {
+    // build the scanner once for the whole file
+    let scanner = DigitScanner::new(vocabulary);
+
+    checked_sum(
+        lines
+            .iter()
+            .filter_map(|line| scanner.calibration_value(line)),
+    )
 }
 
 pub fn puz1() {
     let lines: Vec<String> = file_io::read_file_to_vec("input/day1-input");
 
-    let mut calibration_value: u32 = 0;
+    let calibration_value =
+        calibrate(&lines, &Vocabulary::digits()).unwrap_or_else(|e| panic!("{e}"));
 
-    for line in lines {
-        let line = &line;
-        // find first and last number character, assemble into number
-        let first_number = match line.chars().find(|c| c.is_numeric()) {
-            Some(c) => c,
-            None => continue,
-        };
+    println!("The calibration value is : {calibration_value}");
+}
 
-        let second_number = match line.chars().rev().find(|c| c.is_numeric()) {
-            Some(c) => c,
-            None => continue,
-        };
+pub fn puz2() {
+    let lines: Vec<String> = file_io::read_file_to_vec("input/day1-input");
 
-        let assembled_number =
-            String::new() + &first_number.to_string() + &second_number.to_string();
+    let calibration_value =
+        calibrate(&lines, &Vocabulary::english()).unwrap_or_else(|e| panic!("{e}"));
 
-        // add to calibration number
-        let number: u32 = assembled_number.parse().expect("Failed to parse number");
+    println!("The calibration value is : {calibration_value}");
+}
 
-        calibration_value = calibration_value + number;
-    }
+/// Any text is a list of lines, so this can't fail.
+pub fn parse(input: &str) -> Result<Vec<String>, Infallible> {
+    Ok(file_io::split_lines(input))
+}
+
+pub fn part1(lines: &[String]) -> Solved {
+    Ok(Answer::from(calibrate(lines, &Vocabulary::digits())?))
+}
+
+pub fn part2(lines: &[String]) -> Solved {
+    Ok(Answer::from(calibrate(lines, &Vocabulary::english())?))
+}
+
+pub fn solution() -> Solution {
+    Solution::new(1, parse, |lines| part1(lines), |lines| part2(lines))
+}
+
+/// Calibrate with the digits plus a user supplied vocabulary file.
+pub fn puz_with_vocabulary(file_path: &str) {
+    let lines: Vec<String> = file_io::read_file_to_vec("input/day1-input");
+    let vocabulary = Vocabulary::from_file(file_path)
+        .unwrap_or_else(|e| panic!("failed to read vocabulary: {e}"));
+
+    let calibration_value =
+        calibrate(&lines, &Vocabulary::digits().with(vocabulary)).unwrap_or_else(|e| panic!("{e}"));
 
     println!("The calibration value is : {calibration_value}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{calibrate, DigitScanner, DigitToken, Vocabulary};
+
+    #[test]
+    fn test_overlapping_words() {
+        let scanner = DigitScanner::new(&Vocabulary::english());
+
+        assert_eq!(
+            vec![
+                DigitToken {
+                    position: 0,
+                    length: 3,
+                    value: 2
+                },
+                DigitToken {
+                    position: 2,
+                    length: 3,
+                    value: 1
+                }
+            ],
+            scanner.tokens("twone")
+        );
+        let tokens = scanner.tokens("xtwone");
+        assert_eq!(
+            vec![(2, "two"), (1, "one")],
+            tokens
+                .iter()
+                .map(|token| (token.digit(), &"xtwone"[token.start()..token.end()]))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(21), scanner.calibration_value("twone"));
+        assert_eq!(Some(82), scanner.calibration_value("eightwo"));
+        assert_eq!(Some(88), scanner.calibration_value("xeightx"));
+        assert_eq!(Some(79), scanner.calibration_value("sevenine"));
+        assert_eq!(None, scanner.calibration_value("abc"));
+    }
+
+    #[test]
+    fn test_digits_only() {
+        let lines: Vec<String> = ["1abc2", "pqr3stu8vwx", "a1b2c3d4e5f", "treb7uchet", "none"]
+            .iter()
+            .map(|s| String::from(*s))
+            .collect();
+
+        assert_eq!(Ok(142), calibrate(&lines, &Vocabulary::digits()));
+    }
+
+    #[test]
+    fn test_example() {
+        let lines: Vec<String> = [
+            "two1nine",
+            "eightwothree",
+            "abcone2threexyz",
+            "xtwone3four",
+            "4nineeightseven2",
+            "zoneight234",
+            "7pqrstsixteen",
+        ]
+        .iter()
+        .map(|s| String::from(*s))
+        .collect();
+
+        assert_eq!(Ok(281), calibrate(&lines, &Vocabulary::english()));
+    }
+
+    #[test]
+    fn test_other_vocabularies() {
+        let scanner = DigitScanner::new(&Vocabulary::english().with(Vocabulary::zero()));
+        assert_eq!(Some(2), scanner.calibration_value("zerotwo"));
+
+        let scanner = DigitScanner::new(&Vocabulary::roman());
+        assert_eq!(Some(48), scanner.calibration_value("xIVxVIII"));
+
+        let german = Vocabulary::spelled(&["eins", "zwei", "drei"], 1);
+        let scanner = DigitScanner::new(&Vocabulary::digits().with(german));
+        assert_eq!(Some(31), scanner.calibration_value("dreiundzwanzig1eins"));
+    }
+
+    #[test]
+    fn test_parse_vocabulary() {
+        let vocabulary = Vocabulary::parse("# french\nun 1\ndeux 2\n\ntrois 3").unwrap();
+        let scanner = DigitScanner::new(&vocabulary);
+
+        assert_eq!(Some(13), scanner.calibration_value("untroisdeuxtrois"));
+        assert_eq!(
+            Err(String::from("line 1: \"ten\" is not a digit")),
+            Vocabulary::parse("dix ten")
+        );
+    }
+}
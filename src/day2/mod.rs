@@ -1,110 +1,414 @@
-use regex::Regex;
+use std::{collections::BTreeMap, fmt};
 
-use crate::common::file_io;
+use nom::{
+    combinator::consumed,
+    sequence::{delimited, separated_pair},
+};
 
-struct Game {
-    id: u32,
-    red: u32,
-    blue: u32,
-    green: u32,
+use crate::{
+    common::{
+        answer::{checked_product, checked_sum, Answer, Overflow, Solved},
+        file_io::read_file_to_string,
+        parse::{lines, list, literal, name, number, parse_all, Expected, PResult, ParseError},
+    },
+    runner::Solution,
+};
+
+/// Number of cubes of each colour, used both for what a game showed and for what a bag holds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bag {
+    cubes: BTreeMap<String, u32>,
 }
 
-struct Pull {
-    red: Option<u32>,
-    blue: Option<u32>,
-    green: Option<u32>,
+impl Bag {
+    /// The bag from the puzzle: 12 red, 13 green and 14 blue cubes.
+    pub fn standard() -> Bag {
+        Bag::parse("12 red, 13 green, 14 blue").expect("standard bag is valid")
+    }
+
+    /// Parse a comma separated list such as `3 blue, 4 red`.
+    pub fn parse(input: &str) -> Result<Bag, ParseError> {
+        parse_all(input, bag)
+    }
+
+    pub fn count(&self, color: &str) -> u32 {
+        self.cubes.get(color).copied().unwrap_or(0)
+    }
+
+    /// Multiply the counts of every colour in the bag together.
+    pub fn power(&self) -> Result<u32, Overflow> {
+        checked_product(self.cubes.values().copied())
+    }
 }
 
-pub fn puz1() {
-    let lines = file_io::read_file_to_vec("input/day2-input");
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = self
+            .cubes
+            .iter()
+            .map(|(color, count)| format!("{count} {color}"))
+            .collect();
+        write!(f, "{}", entries.join(", "))
+    }
+}
 
-    const MAX_RED: u32 = 12;
-    const MAX_GREEN: u32 = 13;
-    const MAX_BLUE: u32 = 14;
+pub struct Game {
+    pub id: u32,
+    // every pull in the order it was made
+    pub pulls: Vec<Bag>,
+}
 
-    let mut sum_of_game_ids = 0;
+#[derive(Debug, PartialEq)]
+pub enum Feasibility {
+    Possible,
+    Impossible {
+        // 1 based index of the first pull that showed too many cubes
+        pull: usize,
+        color: String,
+        count: u32,
+        limit: u32,
+    },
+}
 
-    for line in lines {
-        let line = parse_line(&line);
+impl Game {
+    /// A game is possible if no pull showed more of a colour than the bag holds. Colours the
+    /// bag doesn't mention count as zero.
+    pub fn is_possible(&self, limits: &Bag) -> bool {
+        self.feasibility(limits) == Feasibility::Possible
+    }
 
-        if line.red <= MAX_RED && line.blue <= MAX_BLUE && line.green <= MAX_GREEN {
-            // game possible
-            sum_of_game_ids = sum_of_game_ids + line.id;
+    /// Find the first pull, and the first colour within it, that the bag couldn't have supplied.
+    pub fn feasibility(&self, limits: &Bag) -> Feasibility {
+        for (index, pull) in self.pulls.iter().enumerate() {
+            for (color, count) in &pull.cubes {
+                let limit = limits.count(color);
+                if *count > limit {
+                    return Feasibility::Impossible {
+                        pull: index + 1,
+                        color: color.clone(),
+                        count: *count,
+                        limit,
+                    };
+                }
+            }
         }
+        Feasibility::Possible
     }
 
-    println!("puz1: Sum of possible game ids {sum_of_game_ids}");
+    /// The fewest cubes of each colour the bag could have held for this game.
+    pub fn minimum_bag(&self) -> Bag {
+        // find highest number of each color in all pulls
+        let mut bag = Bag::default();
+        for pull in &self.pulls {
+            for (color, number) in &pull.cubes {
+                let seen = bag.cubes.entry(color.clone()).or_insert(0);
+                *seen = (*seen).max(*number);
+            }
+        }
+        bag
+    }
+
+    /// Power of the minimum bag over the standard bag's red, green and blue. A colour the game
+    /// never showed counts as 0, and colours the standard bag doesn't hold don't take part.
+    pub fn power(&self) -> Result<u32, Overflow> {
+        let minimum = self.minimum_bag();
+        checked_product(
+            Bag::standard()
+                .cubes
+                .keys()
+                .map(|color| minimum.count(color)),
+        )
+    }
+
+    /// One line saying whether the game fits in `limits`, what broke it if not, and the
+    /// minimum bag for each colour.
+    pub fn report(&self, limits: &Bag) -> String {
+        let verdict = match self.feasibility(limits) {
+            Feasibility::Possible => String::from("possible"),
+            Feasibility::Impossible {
+                pull,
+                color,
+                count,
+                limit,
+            } => {
+                format!("impossible, pull {pull} showed {count} {color} but the bag holds {limit}")
+            }
+        };
+        format!(
+            "Game {}: {verdict}; minimum bag {}",
+            self.id,
+            self.minimum_bag()
+        )
+    }
 }
 
-pub fn puz2() {
-    let lines = file_io::read_file_to_vec("input/day2-input");
+/// A game that didn't parse, with the game and 1 based pull it went wrong in when it got far
+/// enough to know them.
+#[derive(Debug, PartialEq)]
+pub struct GameParseError {
+    pub game: Option<u32>,
+    pub pull: Option<usize>,
+    pub error: ParseError,
+}
+
+impl GameParseError {
+    /// Work out which game and pull the failure in `error` falls in from the line it points at.
+    fn locate(error: ParseError) -> GameParseError {
+        let header = error
+            .text
+            .strip_prefix("Game ")
+            .and_then(|rest| rest.split_once(": "))
+            .map(|(id, _)| (id.parse().ok(), "Game : ".len() + id.chars().count()));
 
-    let mut sum_of_game_powers = 0;
-    for line in lines {
-        let line = parse_line(&line);
+        // pulls only start once the header has been read
+        let pull = header
+            .filter(|(_, length)| error.column > *length)
+            .map(|(_, length)| {
+                let before: String = error.text.chars().take(error.column - 1).collect();
+                before[length..].matches(';').count() + 1
+            });
 
-        let game_power = line.blue * line.green * line.red;
-        sum_of_game_powers = sum_of_game_powers + game_power;
+        GameParseError {
+            game: header.and_then(|(game, _)| game),
+            pull,
+            error,
+        }
     }
+}
+
+impl fmt::Display for GameParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(game) = self.game {
+            write!(f, "game {game}, ")?;
+        }
+        if let Some(pull) = self.pull {
+            write!(f, "pull {pull}, ")?;
+        }
+        write!(f, "{}", self.error)
+    }
+}
+
+pub fn puz1() {
+    let input = read_file_to_string("input/day2-input");
+    let games = parse_games(&input).unwrap_or_else(|e| panic!("failed to parse games: {e}"));
+
+    let limits = Bag::standard();
+
+    for game in &games {
+        println!("{}", game.report(&limits));
+    }
+
+    let sum_of_game_ids: u32 = games
+        .iter()
+        .filter(|game| game.is_possible(&limits))
+        .map(|game| game.id)
+        .sum();
+
+    println!("puz1: Sum of possible game ids {sum_of_game_ids}");
+}
+
+pub fn puz2() {
+    let input = read_file_to_string("input/day2-input");
+    let games = parse_games(&input).unwrap_or_else(|e| panic!("failed to parse games: {e}"));
+
+    let sum_of_game_powers: u32 = games
+        .iter()
+        .map(|game| game.power().unwrap_or_else(|e| panic!("{e}")))
+        .sum();
 
     println!("puz2: Sum of game powers: {sum_of_game_powers}");
 }
 
-fn parse_line(line: &str) -> Game {
-    let matcher = Regex::new("^Game ([0-9]*):(.*)$");
-    let re = matcher.unwrap();
-    let capture = re.captures(line).expect("failed to parse line");
-    let id = capture.get(1).expect("couldnt find id").as_str();
-    let pulls = capture.get(2).expect("couldn't get games").as_str();
-
-    let parsed_pulls: Vec<Pull> = pulls
-        .split(";")
-        .map(|s| s.trim())
-        .map(|s| parse_pull(s))
-        .collect();
-
-    // find highest number of each color in all games played
-    let mut game = Game {
-        id: id.parse().expect("game is not a number"),
-        red: 0,
-        green: 0,
-        blue: 0,
-    };
-    for pull in parsed_pulls {
-        if pull.red.unwrap_or(0) > game.red {
-            game.red = pull.red.unwrap()
-        }
+pub fn parse(input: &str) -> Result<Vec<Game>, GameParseError> {
+    parse_games(input)
+}
 
-        if pull.blue.unwrap_or(0) > game.blue {
-            game.blue = pull.blue.unwrap()
-        }
+pub fn part1(games: &[Game]) -> Solved {
+    let limits = Bag::standard();
+    let possible_game_ids = games
+        .iter()
+        .filter(|game| game.is_possible(&limits))
+        .map(|game| game.id);
+    Ok(Answer::from(checked_sum(possible_game_ids)?))
+}
+
+pub fn part2(games: &[Game]) -> Solved {
+    let powers = games
+        .iter()
+        .map(|game| game.power())
+        .collect::<Result<Vec<u32>, Overflow>>()?;
+    Ok(Answer::from(checked_sum(powers)?))
+}
+
+pub fn solution() -> Solution {
+    Solution::new(2, parse, |games| part1(games), |games| part2(games))
+}
+
+fn parse_games(input: &str) -> Result<Vec<Game>, GameParseError> {
+    parse_all(input, lines(game)).map_err(GameParseError::locate)
+}
+
+/// `Game <id>: ` followed by pulls separated by semicolons.
+fn game(input: &str) -> PResult<'_, Game> {
+    let (input, id) = delimited(literal("Game "), number, literal(": "))(input)?;
+    let (input, pulls) = list(literal("; "), bag)(input)?;
 
-        if pull.green.unwrap_or(0) > game.green {
-            game.green = pull.green.unwrap()
+    Ok((input, Game { id, pulls }))
+}
+
+/// `<count> <colour>` separated by commas, each colour at most once.
+fn bag(input: &str) -> PResult<'_, Bag> {
+    let cube = separated_pair(number, literal(" "), name);
+    let (rest, cubes) = list(literal(", "), consumed(cube))(input)?;
+
+    let mut bag = Bag::default();
+    for (text, (count, color)) in cubes {
+        if bag.cubes.insert(String::from(color), count).is_some() {
+            return Err(nom::Err::Failure(Expected::new(
+                text,
+                format!("{color} only once per pull"),
+            )));
         }
     }
-    game
+
+    Ok((rest, bag))
 }
 
-fn parse_pull(game: &str) -> Pull {
-    let pulls: Vec<&str> = game.split(",").map(|s| s.trim()).collect();
-    let mut parsed_pull = Pull {
-        red: None,
-        blue: None,
-        green: None,
-    };
+#[cfg(test)]
+mod tests {
+    use crate::{common::parse::ParseError, runner::example};
 
-    for pull in pulls {
-        let split: Vec<&str> = pull.split(" ").collect();
-        let number: u32 = split[0].parse().expect("failed to parse number of cubes");
-        let color = split[1];
+    use super::{parse_games, Bag, Feasibility, GameParseError};
 
-        match color {
-            "red" => parsed_pull.red = Some(number),
-            "blue" => parsed_pull.blue = Some(number),
-            "green" => parsed_pull.green = Some(number),
-            _ => panic!("unexpected color {color}"),
-        }
+    #[test]
+    fn test_possible_games() {
+        let games = parse_games(&example(2, "example")).unwrap();
+
+        let limits = Bag::standard();
+        let possible: Vec<u32> = games
+            .iter()
+            .filter(|game| game.is_possible(&limits))
+            .map(|game| game.id)
+            .collect();
+
+        assert_eq!(vec![1, 2, 5], possible);
+    }
+
+    #[test]
+    fn test_powers() {
+        let games = parse_games(&example(2, "example")).unwrap();
+
+        let powers: Vec<u32> = games.iter().map(|game| game.power().unwrap()).collect();
+
+        assert_eq!(vec![48, 12, 1560, 630, 36], powers);
+        assert_eq!(
+            Bag::parse("4 red, 2 green, 6 blue").unwrap(),
+            games[0].minimum_bag()
+        );
+    }
+
+    #[test]
+    fn test_first_pull_over_limit() {
+        let games = parse_games(&example(2, "example")).unwrap();
+
+        assert_eq!(
+            Feasibility::Possible,
+            games[0].feasibility(&Bag::standard())
+        );
+        assert_eq!(
+            Feasibility::Impossible {
+                pull: 1,
+                color: String::from("red"),
+                count: 20,
+                limit: 12
+            },
+            games[2].feasibility(&Bag::standard())
+        );
+        assert_eq!(
+            Feasibility::Impossible {
+                pull: 3,
+                color: String::from("blue"),
+                count: 15,
+                limit: 14
+            },
+            games[3].feasibility(&Bag::standard())
+        );
+        assert_eq!(3, games[2].pulls.len());
+    }
+
+    #[test]
+    fn test_report() {
+        let games = parse_games(&example(2, "example")).unwrap();
+
+        assert_eq!(
+            "Game 1: possible; minimum bag 6 blue, 2 green, 4 red",
+            games[0].report(&Bag::standard())
+        );
+        assert_eq!(
+            "Game 3: impossible, pull 1 showed 20 red but the bag holds 12; minimum bag 6 blue, 13 green, 20 red",
+            games[2].report(&Bag::standard())
+        );
+    }
+
+    #[test]
+    fn test_other_colours() {
+        let games = parse_games("Game 7: 3 purple, 1 red; 5 purple").unwrap();
+
+        assert_eq!(Ok(0), games[0].power());
+        assert!(!games[0].is_possible(&Bag::standard()));
+        assert!(games[0].is_possible(&Bag::parse("5 purple, 1 red").unwrap()));
+    }
+
+    #[test]
+    fn test_power_counts_missing_colours_as_zero() {
+        let games =
+            parse_games("Game 1: 3 blue, 4 red; 2 red\nGame 2: 1 green, 2 blue, 3 red").unwrap();
+
+        assert_eq!(Ok(0), games[0].power());
+        assert_eq!(Ok(6), games[1].power());
+    }
+
+    #[test]
+    fn test_parse_error_points_at_pull() {
+        let input = "Game 1: 3 blue, 4 red\nGame 2: 1 blue; 2 green, lots red";
+
+        let error = parse_games(input).err().unwrap();
+        assert_eq!(
+            GameParseError {
+                game: Some(2),
+                pull: Some(2),
+                error: ParseError {
+                    line: 2,
+                    column: 26,
+                    text: String::from("Game 2: 1 blue; 2 green, lots red"),
+                    expected: String::from("a number"),
+                },
+            },
+            error
+        );
+        assert!(error
+            .to_string()
+            .starts_with("game 2, pull 2, line 2, column 26: expected a number"));
+    }
+
+    #[test]
+    fn test_parse_error_before_first_pull() {
+        let error = parse_games(
+            "Game 1: 3 blue
+Game x: 1 red",
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!((None, None), (error.game, error.pull));
+        assert_eq!(2, error.error.line);
+    }
+
+    #[test]
+    fn test_colour_repeated_in_pull() {
+        let error = Bag::parse("3 blue, 4 red, 1 blue").unwrap_err();
+
+        assert_eq!(16, error.column);
+        assert_eq!("blue only once per pull", error.expected);
     }
-    parsed_pull
 }
@@ -1,6 +1,18 @@
-use std::{char, collections::HashMap};
-
-use crate::common::file_io::read_file_to_vec;
+use std::{
+    char,
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufRead, BufReader},
+};
+
+use crate::{
+    common::{
+        answer::{checked_product, checked_sum, Answer, Overflow, Solved},
+        file_io::{read_file_to_vec, split_lines},
+        parse::ParseError,
+    },
+    runner::Solution,
+};
 
 #[derive(Debug)]
 enum GridElement {
@@ -20,31 +32,198 @@ impl GridElement {
     }
 }
 
-struct Grid {
-    grid: Vec<Vec<GridElement>>,
+/// A run of digits on one row of the schematic. `end` is the column of the last digit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberSpan {
+    row: usize,
+    start: usize,
+    end: usize,
+    value: u32,
 }
 
-impl Grid {
-    fn is_symbol(&self, row: i32, column: i32) -> bool {
-        // clamp the values
-        if row < 0 {
-            return false;
+impl NumberSpan {
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    /// The row, and the first and last columns, the number covers.
+    pub fn position(&self) -> (usize, usize, usize) {
+        (self.row, self.start, self.end)
+    }
+
+    /// True if the cell at `row`, `column` touches this number, diagonals included.
+    pub fn is_adjacent(&self, row: usize, column: usize) -> bool {
+        row + 1 >= self.row
+            && row <= self.row + 1
+            && column + 1 >= self.start
+            && column <= self.end + 1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Symbol {
+    row: usize,
+    column: usize,
+    symbol: char,
+}
+
+impl Symbol {
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn symbol(&self) -> char {
+        self.symbol
+    }
+}
+
+/// Pull every number span and symbol out of one row of the grid, or the column a number too big
+/// for a `u32` starts at.
+fn extract_row(row: usize, line: &[GridElement]) -> Result<(Vec<NumberSpan>, Vec<Symbol>), usize> {
+    let mut numbers = Vec::new();
+    let mut symbols = Vec::new();
+
+    let mut current: Option<NumberSpan> = None;
+
+    for (column, item) in line.iter().enumerate() {
+        match item {
+            GridElement::NUMBER(n) => {
+                let digit = n.to_digit(10).expect("NUMBER holds a digit");
+                match current.as_mut() {
+                    Some(span) => {
+                        span.end = column;
+                        span.value = span
+                            .value
+                            .checked_mul(10)
+                            .and_then(|value| value.checked_add(digit))
+                            .ok_or(span.start)?;
+                    }
+                    None => {
+                        current = Some(NumberSpan {
+                            row,
+                            start: column,
+                            end: column,
+                            value: digit,
+                        })
+                    }
+                }
+                continue;
+            }
+            GridElement::SYMBOL(c) => symbols.push(Symbol {
+                row,
+                column,
+                symbol: *c,
+            }),
+            GridElement::BLANK => {}
         }
-        if column < 0 {
-            return false;
+        // anything that isn't a digit ends the current number
+        numbers.extend(current.take());
+    }
+
+    // a number running into the end of the line
+    numbers.extend(current.take());
+
+    Ok((numbers, symbols))
+}
+
+/// How many neighbouring numbers a symbol needs to match a [`SymbolQuery`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NeighbourRule {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl NeighbourRule {
+    pub fn allows(&self, count: usize) -> bool {
+        match self {
+            NeighbourRule::Exactly(n) => count == *n,
+            NeighbourRule::AtLeast(n) => count >= *n,
         }
+    }
+}
 
-        let row: usize = row as usize;
-        let column: usize = column as usize;
+/// How the numbers around a matching symbol are combined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reduce {
+    Product,
+    Sum,
+    Max,
+}
 
-        if row >= self.grid.len() || column >= self.grid[row].len() {
-            false
-        } else {
-            matches!(self.grid[row][column], GridElement::SYMBOL(_))
+impl Reduce {
+    pub fn apply(&self, numbers: &[&NumberSpan]) -> Result<u32, Overflow> {
+        let values = numbers.iter().map(|number| number.value);
+        match self {
+            Reduce::Product => checked_product(values),
+            Reduce::Sum => checked_sum(values),
+            Reduce::Max => Ok(values.max().unwrap_or(0)),
+        }
+    }
+}
+
+pub struct SymbolQuery {
+    // symbols to look at, empty means every symbol
+    symbols: Vec<char>,
+    rule: NeighbourRule,
+    reduce: Reduce,
+}
+
+impl SymbolQuery {
+    pub fn new(symbols: &[char], rule: NeighbourRule, reduce: Reduce) -> SymbolQuery {
+        SymbolQuery {
+            symbols: symbols.to_vec(),
+            rule,
+            reduce,
         }
     }
 
-    fn is_gear(&self, row: i32, column: i32) -> bool {
+    /// The puzzle's gears: `*` next to exactly two numbers, multiplied together.
+    pub fn gears() -> SymbolQuery {
+        SymbolQuery::new(&['*'], NeighbourRule::Exactly(2), Reduce::Product)
+    }
+
+    fn matches_symbol(&self, symbol: char) -> bool {
+        self.symbols.is_empty() || self.symbols.contains(&symbol)
+    }
+}
+
+#[derive(Debug)]
+pub struct SymbolMatch<'a> {
+    symbol: &'a Symbol,
+    numbers: Vec<&'a NumberSpan>,
+    value: Result<u32, Overflow>,
+}
+
+impl<'a> SymbolMatch<'a> {
+    pub fn symbol(&self) -> &'a Symbol {
+        self.symbol
+    }
+
+    /// The numbers touching the symbol, in row then column order.
+    pub fn numbers(&self) -> &[&'a NumberSpan] {
+        &self.numbers
+    }
+
+    /// The numbers reduced the way the query asked for.
+    pub fn value(&self) -> Result<u32, Overflow> {
+        self.value.clone()
+    }
+}
+
+pub struct Grid {
+    grid: Vec<Vec<GridElement>>,
+    // every number in row then column order
+    numbers: Vec<NumberSpan>,
+    // every symbol in row then column order
+    symbols: Vec<Symbol>,
+}
+
+impl Grid {
+    fn is_symbol(&self, row: i32, column: i32) -> bool {
         // clamp the values
         if row < 0 {
             return false;
@@ -59,244 +238,171 @@ impl Grid {
         if row >= self.grid.len() || column >= self.grid[row].len() {
             false
         } else {
-            matches!(self.grid[row][column], GridElement::SYMBOL('*'))
+            matches!(self.grid[row][column], GridElement::SYMBOL(_))
         }
     }
 
-    fn is_adjacent(&self, row: usize, column: usize) -> bool {
-        let row: i32 = row as i32;
-        let column: i32 = column as i32;
-        self.is_symbol(row - 1, column - 1)
-            || self.is_symbol(row - 1, column)
-            || self.is_symbol(row - 1, column + 1)
-            || self.is_symbol(row, column - 1)
-            // || self.is_symbol(row, column)
-            || self.is_symbol(row, column + 1)
-            || self.is_symbol(row + 1, column - 1)
-            || self.is_symbol(row + 1, column)
-            || self.is_symbol(row + 1, column + 1)
-    }
-
-    fn is_adjacent_to_gear(&self, row: usize, column: usize) -> Vec<(usize, usize)> {
-        let row: i32 = row as i32;
-        let column: i32 = column as i32;
-        let check_grid = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
-        let mut gear_locs: Vec<(usize, usize)> = Vec::new();
-        for grid_coord in check_grid {
-            if self.is_gear(row + grid_coord.0, column + grid_coord.1) {
-                gear_locs.push((
-                    (row + grid_coord.0) as usize,
-                    (column + grid_coord.1) as usize,
-                ));
-            }
-        }
-        gear_locs
+    /// Whether any cell around the number holds a symbol.
+    fn is_part(&self, number: &NumberSpan) -> bool {
+        let row = number.row as i32;
+        (row - 1..=row + 1).any(|r| {
+            (number.start as i32 - 1..=number.end as i32 + 1).any(|c| self.is_symbol(r, c))
+        })
     }
 
-    pub fn total_gear_ratio(&self) -> u32 {
-        let mut parser_row_index = 0;
-        let mut parser_column_index = 0;
+    /// Numbers adjacent to at least one symbol.
+    pub fn part_numbers(&self) -> Vec<&NumberSpan> {
+        self.numbers
+            .iter()
+            .filter(|number| self.is_part(number))
+            .collect()
+    }
 
-        let mut in_number = false;
-        let mut current_number = String::new();
+    /// Numbers touching the cell at `row`, `column`.
+    pub fn numbers_adjacent_to(&self, row: usize, column: usize) -> Vec<&NumberSpan> {
+        // numbers are sorted by row so only rows either side of the cell need checking
+        let first = self.numbers.partition_point(|n| n.row + 1 < row);
+        let last = self.numbers.partition_point(|n| n.row <= row + 1);
 
-        let mut total_gear_ratio: u32 = 0;
+        self.numbers[first..last]
+            .iter()
+            .filter(|number| number.is_adjacent(row, column))
+            .collect()
+    }
 
-        let mut gear_map: HashMap<(usize, usize), Vec<u32>> = HashMap::new();
-        let mut current_number_gear_list = Vec::new();
+    /// Every symbol matching `query`, with its neighbouring numbers reduced to a single value.
+    pub fn query(&self, query: &SymbolQuery) -> Vec<SymbolMatch<'_>> {
+        self.symbols
+            .iter()
+            .filter(|symbol| query.matches_symbol(symbol.symbol))
+            .map(|symbol| (symbol, self.numbers_adjacent_to(symbol.row, symbol.column)))
+            .filter(|(_, numbers)| query.rule.allows(numbers.len()))
+            .map(|(symbol, numbers)| SymbolMatch {
+                symbol,
+                value: query.reduce.apply(&numbers),
+                numbers,
+            })
+            .collect()
+    }
 
-        while parser_row_index < self.grid.len() {
-            while parser_column_index < self.grid[parser_row_index].len() {
-                let item = &self.grid[parser_row_index][parser_column_index];
+    /// Symbols of any kind with exactly `count` numbers next to them.
+    pub fn symbols_with_neighbours(&self, count: usize) -> Vec<SymbolMatch<'_>> {
+        self.query(&SymbolQuery::new(
+            &[],
+            NeighbourRule::Exactly(count),
+            Reduce::Sum,
+        ))
+    }
 
-                if in_number {
-                    // if we're in a number and this is a number add it on
-                    if let GridElement::NUMBER(n) = item {
-                        current_number.push(*n);
-                        in_number = true;
-                        // check adj to gears and record
-                        let gears = self.is_adjacent_to_gear(parser_row_index, parser_column_index);
-                        for gear in gears {
-                            if !current_number_gear_list.contains(&gear) {
-                                current_number_gear_list.push(gear);
-                            }
-                        }
-                    } else {
-                        // compute full number
-                        let number = current_number
-                            .parse::<u32>()
-                            .expect("Failed to parse number");
-
-                        // record this number against all the gears we came across
-                        for gear in &current_number_gear_list {
-                            if gear_map.contains_key(&gear) {
-                                let gear_list =
-                                    gear_map.get_mut(&gear).expect("failed to get gear list");
-                                gear_list.push(number);
-                            } else {
-                                let mut gear_list: Vec<u32> = Vec::new();
-                                gear_list.push(number);
-                                gear_map.insert(*gear, gear_list);
-                            }
-                        }
-
-                        // no longer in a number
-                        in_number = false;
-                        current_number.clear();
-                        current_number_gear_list.clear();
-                    }
-                    // check for adjacent symbols
-                } else {
-                    // start of number?
-                    if let GridElement::NUMBER(n) = item {
-                        current_number.push(*n);
-                        in_number = true;
-                        // check adj to gears and record
-                        let gears = self.is_adjacent_to_gear(parser_row_index, parser_column_index);
-                        for gear in gears {
-                            if !current_number_gear_list.contains(&gear) {
-                                current_number_gear_list.push(gear);
-                            }
-                        }
-                    }
-                }
+    /// `*` symbols next to exactly two numbers.
+    pub fn gears(&self) -> Vec<SymbolMatch<'_>> {
+        self.query(&SymbolQuery::gears())
+    }
 
-                parser_column_index = parser_column_index + 1;
-            }
+    pub fn total_gear_ratio(&self) -> Result<u32, Overflow> {
+        let ratios = self
+            .gears()
+            .into_iter()
+            .map(|gear| gear.value)
+            .collect::<Result<Vec<u32>, Overflow>>()?;
+        checked_sum(ratios)
+    }
 
-            // if we're in a number at this point perform the end of line calc
-            if in_number {
-                // compute full number
-                let number = current_number
-                    .parse::<u32>()
-                    .expect("Failed to parse number");
-
-                // record this number against all the gears we came across
-                for gear in &current_number_gear_list {
-                    if gear_map.contains_key(&gear) {
-                        let gear_list = gear_map.get_mut(&gear).expect("failed to get gear list");
-                        gear_list.push(number);
+    pub fn total_parts(&self) -> Result<u32, Overflow> {
+        checked_sum(self.part_numbers().iter().map(|number| number.value))
+    }
+
+    /// Draw the schematic with part numbers, other numbers and gears marked out, followed by a
+    /// list of every gear and the numbers that make its ratio.
+    pub fn render(&self, style: RenderStyle) -> String {
+        let gears = self.gears();
+        let is_gear = |row: usize, column: usize| {
+            gears
+                .iter()
+                .any(|gear| gear.symbol.row == row && gear.symbol.column == column)
+        };
+
+        let mut output = String::new();
+        let mut numbers = self.numbers.iter().peekable();
+
+        for (row, line) in self.grid.iter().enumerate() {
+            let mut column = 0;
+            while column < line.len() {
+                if let Some(number) = numbers.next_if(|n| n.row == row && n.start == column) {
+                    let mark = if self.is_part(number) {
+                        Mark::Part
                     } else {
-                        let mut gear_list: Vec<u32> = Vec::new();
-                        gear_list.push(number);
-                        gear_map.insert(*gear, gear_list);
-                    }
+                        Mark::NotPart
+                    };
+                    // draw the digits as written so leading zeros survive
+                    let digits: String = line[number.start..=number.end]
+                        .iter()
+                        .filter_map(|element| match element {
+                            GridElement::NUMBER(c) => Some(*c),
+                            _ => None,
+                        })
+                        .collect();
+                    output += &style.mark(&digits, mark);
+                    column = number.end + 1;
+                    continue;
                 }
 
-                // no longer in a number
-                in_number = false;
-                current_number.clear();
-                current_number_gear_list.clear();
+                match line[column] {
+                    GridElement::SYMBOL(c) if is_gear(row, column) => {
+                        output += &style.mark(&c.to_string(), Mark::Gear)
+                    }
+                    GridElement::SYMBOL(c) | GridElement::NUMBER(c) => output.push(c),
+                    GridElement::BLANK => output.push('.'),
+                }
+                column += 1;
             }
-
-            parser_row_index = parser_row_index + 1;
-            parser_column_index = 0;
-            current_number.clear();
-            in_number = false;
-            current_number_gear_list.clear();
+            output.push('\n');
         }
 
-        // having iterated we can go through the gear map to find all gears that were connected to exactly 2 numbers
-        // multiply those together and sum for the gear ratio
-        for (gear, number_list) in gear_map.iter() {
-            println!("{gear:?} adjacent to numbers {number_list:?}");
-            if number_list.len() == 2 {
-                total_gear_ratio = total_gear_ratio + (number_list[0] * number_list[1]);
-            }
+        output += "\nGears:\n";
+        for gear in &gears {
+            let numbers: Vec<String> = gear.numbers.iter().map(|n| n.value.to_string()).collect();
+            output += &format!(
+                "  ({}, {}) {} = {}\n",
+                gear.symbol.row,
+                gear.symbol.column,
+                numbers.join(" x "),
+                gear.value
+                    .as_ref()
+                    .map_or_else(|overflow| overflow.to_string(), |value| value.to_string())
+            );
         }
 
-        total_gear_ratio
+        output
     }
+}
 
-    pub fn total_parts(&self) -> u32 {
-        // when finding a symbol check adjasent locations for numbers, on finding a number
-        // scan left and right to find the start and end of the number then parse that slice
-
-        let mut parser_row_index = 0;
-        let mut parser_column_index = 0;
-
-        let mut in_number = false;
-        let mut current_number = String::new();
-        let mut current_number_symbol_adj = false;
-
-        let mut total_parts: u32 = 0;
-
-        while parser_row_index < self.grid.len() {
-            while parser_column_index < self.grid[parser_row_index].len() {
-                let item = &self.grid[parser_row_index][parser_column_index];
-
-                if in_number {
-                    // if we're in a number and this is a number add it on
-                    if let GridElement::NUMBER(n) = item {
-                        current_number.push(*n);
-                        in_number = true;
-                        // check adj to symbol
-                        if self.is_adjacent(parser_row_index, parser_column_index) {
-                            current_number_symbol_adj = true;
-                        }
-                    } else {
-                        // compute full number
-
-                        // if we were adjancent to a symbol at any point add to total
-                        println!("{current_number} adj {current_number_symbol_adj}");
-                        if current_number_symbol_adj {
-                            total_parts = total_parts
-                                + current_number
-                                    .parse::<u32>()
-                                    .expect("Failed to parse number");
-                        }
-
-                        // no longer in a number
-                        in_number = false;
-                        current_number.clear();
-                        current_number_symbol_adj = false;
-                    }
-                    // check for adjacent symbols
-                } else {
-                    // start of number?
-                    if let GridElement::NUMBER(n) = item {
-                        current_number.push(*n);
-                        in_number = true;
-                        // check adj to symbol
-                        if self.is_adjacent(parser_row_index, parser_column_index) {
-                            current_number_symbol_adj = true;
-                        }
-                    }
-                }
-
-                parser_column_index = parser_column_index + 1;
-            }
+/// How [`Grid::render`] marks out cells.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderStyle {
+    /// Terminal colours: part numbers green, other numbers red, gears yellow.
+    Ansi,
+    /// Brackets: `[part]`, `(not a part)`, `{gear}`.
+    Plain,
+}
 
-            // if we're in a number at this point perform the end of line calc
-            if in_number {
-                // compute full number
-
-                // if we were adjancent to a symbol at any point add to total
-                println!("{current_number} adj {current_number_symbol_adj}");
-                if current_number_symbol_adj {
-                    total_parts = total_parts
-                        + current_number
-                            .parse::<u32>()
-                            .expect("Failed to parse number");
-                }
-            }
+#[derive(Debug, Clone, Copy)]
+enum Mark {
+    Part,
+    NotPart,
+    Gear,
+}
 
-            parser_row_index = parser_row_index + 1;
-            parser_column_index = 0;
-            current_number.clear();
-            in_number = false;
-            current_number_symbol_adj = false;
+impl RenderStyle {
+    fn mark(&self, text: &str, mark: Mark) -> String {
+        match (self, mark) {
+            (RenderStyle::Ansi, Mark::Part) => format!("\x1b[32m{text}\x1b[0m"),
+            (RenderStyle::Ansi, Mark::NotPart) => format!("\x1b[31m{text}\x1b[0m"),
+            (RenderStyle::Ansi, Mark::Gear) => format!("\x1b[1;33m{text}\x1b[0m"),
+            (RenderStyle::Plain, Mark::Part) => format!("[{text}]"),
+            (RenderStyle::Plain, Mark::NotPart) => format!("({text})"),
+            (RenderStyle::Plain, Mark::Gear) => format!("{{{text}}}"),
         }
-        total_parts
     }
 }
 
@@ -304,66 +410,229 @@ pub fn puz1() {
     let grid = read_file_to_vec("input/day3-input");
 
     // scan vector for symbols (symbols are not numbers and not periods)
-    let grid = parse_grid(grid);
-    let sum = grid.total_parts();
+    let grid = parse_grid(grid).unwrap_or_else(|e| panic!("failed to parse schematic: {e}"));
+    let sum = grid
+        .total_parts()
+        .unwrap_or_else(|overflow| panic!("puz1: {overflow}"));
     println!("Puz1: Sum of all parts {sum}");
 }
 
 pub fn puz2() {
     let grid = read_file_to_vec("input/day3-input");
 
-    let grid = parse_grid(grid);
-    let sum = grid.total_gear_ratio();
+    let grid = parse_grid(grid).unwrap_or_else(|e| panic!("failed to parse schematic: {e}"));
+    let sum = grid
+        .total_gear_ratio()
+        .unwrap_or_else(|overflow| panic!("puz2: {overflow}"));
     println!("Puz1: Sum of all gear ratios {sum}");
 }
 
-fn parse_grid(grid: Vec<String>) -> Grid {
+pub fn parse(input: &str) -> Result<Grid, ParseError> {
+    parse_grid(split_lines(input))
+}
+
+pub fn part1(grid: &Grid) -> Solved {
+    let part_numbers = grid.part_numbers().into_iter().map(|number| number.value);
+    Ok(Answer::from(checked_sum(part_numbers)?))
+}
+
+pub fn part2(grid: &Grid) -> Solved {
+    Ok(Answer::from(grid.total_gear_ratio()?))
+}
+
+pub fn solution() -> Solution {
+    Solution::new(3, parse, part1, part2)
+}
+
+/// The numbers and symbols of one row, all the streaming parser keeps of it.
+struct StreamRow {
+    numbers: Vec<NumberSpan>,
+    symbols: Vec<Symbol>,
+}
+
+/// Part and gear ratio totals for a schematic read one row at a time. Only the rows either side
+/// of the one being totalled are held, so memory stays flat however tall the grid is.
+pub fn stream_totals<R: BufRead>(mut reader: R) -> io::Result<(u64, u64)> {
+    let mut window: VecDeque<StreamRow> = VecDeque::with_capacity(3);
+    let mut total_parts: u64 = 0;
+    let mut total_gear_ratio: u64 = 0;
+
+    let mut line = String::new();
+    let mut row = 0;
+    loop {
+        line.clear();
+        let more = reader.read_line(&mut line)? > 0;
+
+        if more {
+            let elements: Vec<GridElement> =
+                line.trim().chars().map(GridElement::from_char).collect();
+            let (numbers, symbols) = extract_row(row, &elements).map_err(|column| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "line {}, column {}: number too big for u32",
+                        row + 1,
+                        column + 1
+                    ),
+                )
+            })?;
+            window.push_back(StreamRow { numbers, symbols });
+            row += 1;
+        }
+
+        // the middle row has both neighbours once three rows are in, the last row needs totalling
+        // on its own once the input runs out
+        let middle = match (more, window.len()) {
+            (true, 3) => 1,
+            (true, _) if row == 2 => 0,
+            (false, 0) => break,
+            (false, n) => n - 1,
+            _ => continue,
+        };
+        let (parts, gears) = total_window_row(&window, middle)
+            .and_then(|(parts, gears)| {
+                Ok((
+                    checked_sum([total_parts, parts])?,
+                    checked_sum([total_gear_ratio, gears])?,
+                ))
+            })
+            .map_err(|overflow| io::Error::new(io::ErrorKind::InvalidData, overflow.to_string()))?;
+        total_parts = parts;
+        total_gear_ratio = gears;
+
+        if !more {
+            break;
+        }
+        if window.len() == 3 {
+            window.pop_front();
+        }
+    }
+
+    Ok((total_parts, total_gear_ratio))
+}
+
+/// Totals for the row at `middle` using the rows around it in the window.
+fn total_window_row(window: &VecDeque<StreamRow>, middle: usize) -> Result<(u64, u64), Overflow> {
+    let neighbours = window.range(middle.saturating_sub(1)..(middle + 2).min(window.len()));
+
+    let symbols: Vec<&Symbol> = neighbours.clone().flat_map(|r| &r.symbols).collect();
+    let numbers: Vec<&NumberSpan> = neighbours.flat_map(|r| &r.numbers).collect();
+
+    let parts = checked_sum(
+        window[middle]
+            .numbers
+            .iter()
+            .filter(|number| symbols.iter().any(|s| number.is_adjacent(s.row, s.column)))
+            .map(|number| number.value as u64),
+    )?;
+
+    let gears = window[middle]
+        .symbols
+        .iter()
+        .filter(|symbol| symbol.symbol == '*')
+        .map(|symbol| {
+            numbers
+                .iter()
+                .filter(|number| number.is_adjacent(symbol.row, symbol.column))
+                .collect::<Vec<_>>()
+        })
+        .filter(|adjacent| adjacent.len() == 2)
+        // two u32s always multiply to something that fits in a u64
+        .map(|adjacent| adjacent[0].value as u64 * adjacent[1].value as u64);
+
+    Ok((parts, checked_sum(gears)?))
+}
+
+/// Stream a schematic file of any size and print both totals.
+pub fn stream(file_path: &str) {
+    let file = File::open(file_path).expect("error reading file");
+    let (parts, gears) =
+        stream_totals(BufReader::new(file)).unwrap_or_else(|e| panic!("stream: {e}"));
+
+    println!("Stream: Sum of all parts {parts}");
+    println!("Stream: Sum of all gear ratios {gears}");
+}
+
+/// Print the annotated schematic, coloured unless `plain` is set.
+pub fn render(plain: bool) {
+    let grid = parse_grid(read_file_to_vec("input/day3-input"))
+        .unwrap_or_else(|e| panic!("failed to parse schematic: {e}"));
+
+    let style = if plain {
+        RenderStyle::Plain
+    } else {
+        RenderStyle::Ansi
+    };
+    print!("{}", grid.render(style));
+}
+
+fn parse_grid(grid: Vec<String>) -> Result<Grid, ParseError> {
     let mut inner_grid: Vec<Vec<GridElement>> = Vec::new();
+    let mut numbers = Vec::new();
+    let mut symbols = Vec::new();
 
-    for line in grid {
+    for (row, line) in grid.iter().enumerate() {
         // iterate the line and parse out the grid elements
-        let grid_line: Vec<GridElement> = line
-            .trim()
-            .chars()
-            .map(|c| GridElement::from_char(c))
-            .collect();
+        let grid_line: Vec<GridElement> = line.trim().chars().map(GridElement::from_char).collect();
+
+        let (row_numbers, row_symbols) =
+            extract_row(row, &grid_line).map_err(|column| ParseError {
+                line: row + 1,
+                column: column + 1,
+                text: String::from(line.trim()),
+                expected: String::from("a number that fits in u32"),
+            })?;
+        numbers.extend(row_numbers);
+        symbols.extend(row_symbols);
+
         inner_grid.push(grid_line);
     }
 
-    Grid { grid: inner_grid }
+    Ok(Grid {
+        grid: inner_grid,
+        numbers,
+        symbols,
+    })
 }
 
 mod tests {
-    use crate::common::file_io::read_file_to_vec;
+    use std::io;
+
+    use crate::{
+        common::file_io::{read_file_to_vec, split_lines},
+        runner::example,
+    };
 
-    use super::parse_grid;
+    use super::{
+        parse_grid, stream_totals, NeighbourRule, NumberSpan, Reduce, RenderStyle, SymbolQuery,
+    };
 
     #[test]
     fn test_total_parts() {
         let test_grid: Vec<String> = create_test_input();
 
-        let grid = parse_grid(test_grid);
-        let total = grid.total_parts();
+        let grid = parse_grid(test_grid).unwrap();
+        let total = grid.total_parts().unwrap();
 
         assert_eq!(4361, total);
     }
 
     #[test]
-    fn test_total_parts_full() {
-        let test_grid: Vec<String> = read_file_to_vec("input/day3-input");
+    fn test_total_parts_from_file() {
+        let test_grid: Vec<String> = read_file_to_vec("examples/day3/example.txt");
 
-        let grid = parse_grid(test_grid);
-        let total = grid.total_parts();
+        let grid = parse_grid(test_grid).unwrap();
+        let total = grid.total_parts().unwrap();
 
-        assert_eq!(538046, total);
+        assert_eq!(4361, total);
     }
 
     #[test]
     fn test_gear_ratio() {
         let test_grid: Vec<String> = create_test_input();
 
-        let grid = parse_grid(test_grid);
-        let total = grid.total_gear_ratio();
+        let grid = parse_grid(test_grid).unwrap();
+        let total = grid.total_gear_ratio().unwrap();
         assert_eq!(467835, total)
     }
 
@@ -384,8 +653,8 @@ mod tests {
             String::from("1.1..503+.56"),
         ];
 
-        let grid = parse_grid(test_grid);
-        let total = grid.total_gear_ratio();
+        let grid = parse_grid(test_grid).unwrap();
+        let total = grid.total_gear_ratio().unwrap();
 
         assert_eq!(6756, total);
     }
@@ -407,24 +676,191 @@ mod tests {
             String::from("1.1..503+.56"),
         ];
 
-        let grid = parse_grid(test_grid);
-        let total = grid.total_parts();
+        let grid = parse_grid(test_grid).unwrap();
+        let total = grid.total_parts().unwrap();
 
         assert_eq!(925, total);
     }
 
+    #[test]
+    fn test_number_spans() {
+        let grid = parse_grid(create_test_input()).unwrap();
+
+        assert_eq!(10, grid.numbers.len());
+        assert_eq!(
+            NumberSpan {
+                row: 0,
+                start: 5,
+                end: 7,
+                value: 114
+            },
+            grid.numbers[1]
+        );
+        assert_eq!(6, grid.symbols.len());
+    }
+
+    #[test]
+    fn test_number_at_end_of_line() {
+        let grid = parse_grid(vec![String::from("..*12"), String::from("7....")]).unwrap();
+
+        assert_eq!(
+            vec![12, 7],
+            grid.numbers.iter().map(|n| n.value).collect::<Vec<u32>>()
+        );
+        assert_eq!(Ok(12), grid.total_parts());
+    }
+
+    #[test]
+    fn test_span_queries() {
+        let grid = parse_grid(create_test_input()).unwrap();
+
+        let near: Vec<u32> = grid
+            .numbers_adjacent_to(1, 3)
+            .iter()
+            .map(|n| n.value)
+            .collect();
+        assert_eq!(vec![467, 35], near);
+
+        let lonely: Vec<(usize, usize)> = grid
+            .symbols_with_neighbours(1)
+            .iter()
+            .map(|m| (m.symbol.row, m.symbol.column))
+            .collect();
+        assert_eq!(vec![(3, 6), (4, 3), (5, 5), (8, 3)], lonely);
+
+        let gears: Vec<(usize, usize)> = grid
+            .gears()
+            .iter()
+            .map(|m| (m.symbol.row, m.symbol.column))
+            .collect();
+        assert_eq!(vec![(1, 3), (8, 5)], gears);
+
+        assert!(!grid
+            .part_numbers()
+            .iter()
+            .any(|n| n.value == 114 || n.value == 58));
+    }
+
+    #[test]
+    fn test_custom_query() {
+        let grid = parse_grid(create_test_input()).unwrap();
+
+        // every symbol touching at least one number, keeping the largest
+        let query = SymbolQuery::new(&[], NeighbourRule::AtLeast(1), Reduce::Max);
+        let values: Vec<u32> = grid
+            .query(&query)
+            .iter()
+            .map(|m| m.value().unwrap())
+            .collect();
+        assert_eq!(vec![467, 633, 617, 592, 664, 755], values);
+
+        // # and + with exactly one neighbour, summed
+        let query = SymbolQuery::new(&['#', '+'], NeighbourRule::Exactly(1), Reduce::Sum);
+        let matches = grid.query(&query);
+        assert_eq!(2, matches.len());
+        assert_eq!('#', matches[0].symbol().symbol());
+        assert_eq!(
+            (3, 6),
+            (matches[0].symbol().row(), matches[0].symbol().column())
+        );
+        assert_eq!(
+            vec![633],
+            matches[0]
+                .numbers()
+                .iter()
+                .map(|n| n.value())
+                .collect::<Vec<u32>>()
+        );
+        assert_eq!(Ok(592), matches[1].value());
+
+        // nothing in the example has three neighbours
+        let query = SymbolQuery::new(&['*'], NeighbourRule::AtLeast(3), Reduce::Product);
+        assert!(grid.query(&query).is_empty());
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let grid = parse_grid(create_test_input()).unwrap();
+
+        let expected = r"[467]..(114)..
+...{*}......
+..[35]..[633].
+......#...
+[617]*......
+.....+.(58).
+..[592].....
+......[755].
+...$.{*}....
+.[664].[598]..
+
+Gears:
+  (1, 3) 467 x 35 = 16345
+  (8, 5) 755 x 598 = 451490
+";
+        assert_eq!(expected, grid.render(RenderStyle::Plain));
+    }
+
+    #[test]
+    fn test_render_keeps_leading_zeros() {
+        let grid = parse_grid(vec![String::from("007*.08")]).unwrap();
+
+        assert!(grid.render(RenderStyle::Plain).starts_with("[007]*.(08)\n"));
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let grid = parse_grid(vec![String::from("1#2.3")]).unwrap();
+
+        assert!(grid
+            .render(RenderStyle::Ansi)
+            .starts_with("\x1b[32m1\x1b[0m#\x1b[32m2\x1b[0m.\x1b[31m3\x1b[0m\n"));
+    }
+
+    #[test]
+    fn test_stream_matches_grid() {
+        let reddit = vec![
+            String::from("12.......*.."),
+            String::from("+.........34"),
+            String::from(".......-12.."),
+            String::from("..78......11"),
+            String::from("..*....60..."),
+            String::from("78.........9"),
+            String::from(".5.....23..$"),
+            String::from("8...90*12..."),
+            String::from("............"),
+            String::from("2.2......12."),
+            String::from(".*.........*"),
+            String::from("1.1..503+.56"),
+        ];
+
+        for input in [create_test_input(), reddit] {
+            let text = input.join("\n");
+            let grid = parse_grid(input).unwrap();
+
+            let (parts, gears) = stream_totals(text.as_bytes()).unwrap();
+            assert_eq!(grid.total_parts().unwrap() as u64, parts);
+            assert_eq!(grid.total_gear_ratio().unwrap() as u64, gears);
+        }
+    }
+
+    #[test]
+    fn test_stream_short_inputs() {
+        assert_eq!((0, 0), stream_totals("".as_bytes()).unwrap());
+        assert_eq!((3, 2), stream_totals("1*2\n".as_bytes()).unwrap());
+        assert_eq!((7, 12), stream_totals("3.\n*4".as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_stream_overflow() {
+        // each gear ratio only just fits in a u64, so the second pushes the total over
+        let gear = "4294967295*4294967295";
+        let error = stream_totals(format!("{gear}\n.\n{gear}").as_bytes()).unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        assert_eq!("overflowed u64", error.to_string());
+    }
+
     fn create_test_input() -> Vec<String> {
-        vec![
-            String::from("467..114.."),
-            String::from("...*......"),
-            String::from("..35..633."),
-            String::from("......#..."),
-            String::from("617*......"),
-            String::from(".....+.58."),
-            String::from("..592....."),
-            String::from("......755."),
-            String::from("...$.*...."),
-            String::from(".664.598.."),
-        ]
+        split_lines(&example(3, "example"))
     }
 }
@@ -1,208 +1,430 @@
-use std::{collections::HashMap, fmt::format};
-
-use regex::Regex;
-
-use crate::common::file_io::read_file_to_vec;
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    fmt,
+};
+
+use nom::{
+    character::complete::{space0, space1},
+    sequence::{delimited, pair, preceded},
+};
+
+use crate::{
+    common::{
+        answer::{checked_sum, Answer, Overflow, Solved},
+        file_io::read_file_to_string,
+        parse::{lines, literal, number, numbers, parse_all, PResult, ParseError},
+    },
+    runner::Solution,
+};
 
 pub fn puz1() {
-    let test_grid: Vec<String> = read_file_to_vec("input/day4-input");
+    let input = read_file_to_string("input/day4-input");
+
+    let cards = parse_cards(&input).unwrap_or_else(|e| panic!("failed to parse cards: {e}"));
 
-    let cards = parse_cards(test_grid);
+    print!("{}", cards.report());
 
-    let total = cards.total();
+    let total = cards
+        .total()
+        .unwrap_or_else(|overflow| panic!("puz1: {overflow}"));
 
     println!("Day3 puz1 total: {total}");
 }
 
 pub fn puz2() {
-    let test_grid: Vec<String> = read_file_to_vec("input/day4-input");
+    let input = read_file_to_string("input/day4-input");
 
-    let cards = parse_cards(test_grid);
+    let cards = parse_cards(&input).unwrap_or_else(|e| panic!("failed to parse cards: {e}"));
 
     let total = cards.recursive_card_count();
 
     println!("Day3 puz2 number of cards: {total}");
 }
 
+pub fn parse(input: &str) -> Result<Scratchcards, CardError> {
+    parse_cards(input)
+}
+
+pub fn part1(cards: &Scratchcards) -> Solved {
+    Ok(Answer::from(cards.total()?))
+}
+
+pub fn part2(cards: &Scratchcards) -> Solved {
+    Ok(Answer::from(cards.card_count_dp()?))
+}
+
+pub fn solution() -> Solution {
+    Solution::new(4, parse, part1, part2)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CardError {
+    Malformed(ParseError),
+    Duplicate(u32),
+    Missing(u32),
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardError::Malformed(error) => write!(f, "not a scratchcard, {error}"),
+            CardError::Duplicate(id) => write!(f, "card {id} appears more than once"),
+            CardError::Missing(id) => write!(f, "card {id} is missing"),
+        }
+    }
+}
+
 #[derive(Debug)]
-struct Scratchcards {
-    cards: HashMap<u32, Scratchcard>,
+pub struct Scratchcards {
+    // cards in id order, card n lives at index n - 1
+    cards: Vec<Scratchcard>,
 }
 
 impl Scratchcards {
+    pub fn card(&self, id: u32) -> Option<&Scratchcard> {
+        self.cards.get((id as usize).checked_sub(1)?)
+    }
+
+    /// Every card in id order.
+    pub fn iter(&self) -> impl Iterator<Item = &Scratchcard> {
+        self.cards.iter()
+    }
+
     pub fn recursive_card_count(&self) -> u32 {
         let mut card_count: u32 = 0;
 
         for card in &self.cards {
-            card_count = card_count + self.desend_card(&card.1);
+            card_count += self.desend_card(card);
         }
 
         card_count
     }
 
+    /// Same count as `recursive_card_count` but worked back from the last card, so each card's
+    /// total is itself plus the already known totals of the cards it wins.
+    pub fn card_count_dp(&self) -> Result<u32, Overflow> {
+        let mut totals = vec![0; self.cards.len()];
+
+        for (index, card) in self.cards.iter().enumerate().rev() {
+            let won = index + 1..(index + 1 + card.total_wins as usize).min(self.cards.len());
+            totals[index] = checked_sum(totals[won].iter().copied().chain([1]))?;
+        }
+
+        checked_sum(totals)
+    }
+
     fn desend_card(&self, card: &Scratchcard) -> u32 {
         let mut cards = 1;
-        // how many wins on this card
-        let wins = card.total_wins;
+        // how many wins on this card, wins past the last card don't copy anything
+        let wins = card.total_wins.min(self.cards.len() as u32 - card.id);
 
         // clone in a number of cards equal to wins and recurse
         let mut draw = 0;
         while draw < wins {
             let next_card = self
-                .cards
-                .get(&(card.id + draw + 1))
-                .expect(format!("failed to draw id {}", card.id + draw + 1).as_str());
+                .card(card.id + draw + 1)
+                .unwrap_or_else(|| panic!("failed to draw id {}", card.id + draw + 1));
             // recurse into the won card
-            cards = cards + self.desend_card(&next_card);
-            draw = draw + 1;
+            cards += self.desend_card(next_card);
+            draw += 1;
         }
         cards
     }
 
-    pub fn total(&self) -> u32 {
-        let mut total: u32 = 0;
-
-        for card in self.cards.iter() {
-            let card = card.1;
-            let mut card_score = 0;
-            let mut next_score = 1;
+    pub fn total(&self) -> Result<u32, Overflow> {
+        let points = self
+            .cards
+            .iter()
+            .map(|card| card.points())
+            .collect::<Result<Vec<u32>, Overflow>>()?;
+        checked_sum(points)
+    }
 
-            for drawn_number in &card.drawn_numbers {
-                if card.winning_numbers.contains(&drawn_number) {
-                    // score points and increment next score
-                    println!(
-                        "Card {} drew {} and scored {}",
-                        card.id, drawn_number, next_score
-                    );
-                    card_score = next_score;
-                    next_score = next_score * 2;
-                }
-            }
+    /// One line per card, in id order, listing the drawn numbers that matched and the points scored.
+    pub fn report(&self) -> String {
+        let mut report = String::new();
 
-            println!("Card {} scored {}", card.id, card_score);
-            total = total + card_score;
+        for card in &self.cards {
+            report += &format!(
+                "Card {} matched {:?} and scored {}\n",
+                card.id,
+                card.matches(),
+                card.points()
+                    .map_or_else(|overflow| overflow.to_string(), |points| points.to_string())
+            );
         }
 
-        total
+        report
     }
 }
 
 #[derive(Debug)]
-struct Scratchcard {
+pub struct Scratchcard {
     id: u32,
     winning_numbers: Vec<u32>,
     drawn_numbers: Vec<u32>,
     total_wins: u32,
 }
 
-fn parse_cards(input: Vec<String>) -> Scratchcards {
-    Scratchcards {
-        cards: input
+impl Scratchcard {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn winning_numbers(&self) -> &[u32] {
+        &self.winning_numbers
+    }
+
+    pub fn drawn_numbers(&self) -> &[u32] {
+        &self.drawn_numbers
+    }
+
+    /// How many drawn numbers are winning numbers.
+    pub fn total_wins(&self) -> u32 {
+        self.total_wins
+    }
+
+    pub fn matches(&self) -> Vec<u32> {
+        self.drawn_numbers
             .iter()
-            .map(|line| line.trim())
-            .map(|line| parse_card(line))
-            .map(|card| (card.id, card))
-            .collect(),
+            .filter(|drawn_number| self.winning_numbers.contains(drawn_number))
+            .copied()
+            .collect()
+    }
+
+    /// One point for the first match, doubled for each match after it.
+    pub fn points(&self) -> Result<u32, Overflow> {
+        match self.total_wins {
+            0 => Ok(0),
+            wins => 1u32.checked_shl(wins - 1).ok_or_else(Overflow::of::<u32>),
+        }
     }
 }
 
-fn parse_card(line: &str) -> Scratchcard {
-    let matcher = Regex::new("^Card *([0-9]*):(.*)$");
-    let re = matcher.unwrap();
-    let captures = re.captures(line).expect("failed to get captures");
-    let card_number = captures.get(1).expect("failed to get game id").as_str();
-
-    let cards: Vec<&str> = captures
-        .get(2)
-        .expect("failed to get cards")
-        .as_str()
-        .split("|")
-        .collect();
-    let winning_cards = cards[0];
-    let drawn_cards = cards[1];
-
-    let winning_cards: Vec<u32> = winning_cards
-        .trim()
-        .split_whitespace()
-        .map(|card| card.parse().expect("failed to parse number"))
-        .collect();
-
-    let drawn_cards: Vec<u32> = drawn_cards
-        .trim()
-        .split_whitespace()
-        .map(|card| card.parse().expect("failed to parse number"))
-        .collect();
-
-    let mut win_count = 0;
-
-    for drawn_number in &drawn_cards {
-        if winning_cards.contains(&drawn_number) {
-            // score points and increment next score
-            win_count = win_count + 1;
+fn parse_cards(input: &str) -> Result<Scratchcards, CardError> {
+    let mut cards: BTreeMap<u32, Scratchcard> = BTreeMap::new();
+
+    for card in parse_all(input, lines(card)).map_err(CardError::Malformed)? {
+        match cards.entry(card.id) {
+            Entry::Occupied(_) => return Err(CardError::Duplicate(card.id)),
+            Entry::Vacant(slot) => {
+                slot.insert(card);
+            }
         }
     }
 
-    Scratchcard {
-        id: card_number.parse().expect("failed to parse card number"),
-        winning_numbers: winning_cards,
-        drawn_numbers: drawn_cards,
-        total_wins: win_count,
+    // ids must run 1, 2, 3... with no gaps so a card's position gives its id
+    for (expected, id) in (1..).zip(cards.keys()) {
+        if *id != expected {
+            return Err(CardError::Missing(expected));
+        }
     }
+
+    Ok(Scratchcards {
+        cards: cards.into_values().collect(),
+    })
 }
 
+/// `Card <id>: <winning numbers> | <drawn numbers>`
+fn card(input: &str) -> PResult<'_, Scratchcard> {
+    let (input, id) = delimited(pair(literal("Card"), space1), number, literal(":"))(input)?;
+    let (input, winning_numbers): (_, Vec<u32>) = preceded(space0, numbers)(input)?;
+    let (input, drawn_numbers): (_, Vec<u32>) =
+        preceded(pair(literal("|"), space0), numbers)(input)?;
+
+    let total_wins = drawn_numbers
+        .iter()
+        .filter(|drawn_number| winning_numbers.contains(drawn_number))
+        .count() as u32;
+
+    Ok((
+        input,
+        Scratchcard {
+            id,
+            winning_numbers,
+            drawn_numbers,
+            total_wins,
+        },
+    ))
+}
+
+#[cfg(test)]
 mod tests {
-    use crate::{common::file_io::read_file_to_vec, day4::parse_cards};
+    use crate::{
+        common::{
+            differential::{check, smaller, without_each, Shrink},
+            file_io::{read_file_to_vec, split_lines},
+            parse::ParseError,
+        },
+        day4::{parse_cards, CardError, Scratchcard, Scratchcards},
+        runner::example,
+    };
+
+    /// Number of wins on each card, which can run past the last card.
+    #[derive(Clone, Debug)]
+    struct Wins(Vec<u32>);
+
+    impl Wins {
+        fn cards(&self) -> Scratchcards {
+            Scratchcards {
+                cards: (1..)
+                    .zip(&self.0)
+                    .map(|(id, wins)| Scratchcard {
+                        id,
+                        winning_numbers: (0..*wins).collect(),
+                        drawn_numbers: (0..*wins).collect(),
+                        total_wins: *wins,
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    impl Shrink for Wins {
+        fn shrink(&self) -> Vec<Wins> {
+            let mut candidates: Vec<Wins> = without_each(&self.0).into_iter().map(Wins).collect();
+            for (index, wins) in self.0.iter().enumerate() {
+                for fewer in smaller(*wins as u64, 0) {
+                    let mut shrunk = self.0.clone();
+                    shrunk[index] = fewer as u32;
+                    candidates.push(Wins(shrunk));
+                }
+            }
+            candidates
+        }
+    }
+
+    #[test]
+    fn test_recursion_matches_dp() {
+        check(
+            "day4 recursion vs dp",
+            200,
+            |rng| {
+                Wins(
+                    (0..rng.between(1, 12))
+                        .map(|_| rng.below(6) as u32)
+                        .collect(),
+                )
+            },
+            |wins| {
+                let cards = wins.cards();
+                let recursive = cards.recursive_card_count();
+                let dp = cards.card_count_dp().unwrap();
+                if recursive == dp {
+                    Ok(())
+                } else {
+                    Err(format!("recursion counted {recursive}, dp counted {dp}"))
+                }
+            },
+        );
+    }
 
     #[test]
     fn test_total_points() {
         let test_grid: Vec<String> = create_test_input();
 
-        let cards = parse_cards(test_grid);
+        let cards = parse_cards(&test_grid.join("\n")).unwrap();
         println!("{cards:?}");
         let total = cards.total();
 
-        assert_eq!(13, total);
+        assert_eq!(Ok(13), total);
     }
 
     #[test]
-    fn test_full_data_points() {
-        let test_grid: Vec<String> = read_file_to_vec("input/day4-input");
+    fn test_data_points_from_file() {
+        let test_grid: Vec<String> = read_file_to_vec("examples/day4/example.txt");
 
-        let cards = parse_cards(test_grid);
+        let cards = parse_cards(&test_grid.join("\n")).unwrap();
 
         let total = cards.total();
 
-        assert_eq!(21558, total);
+        assert_eq!(Ok(13), total);
     }
 
     #[test]
     fn test_recursive_card_count_test_input() {
         let test_grid: Vec<String> = create_test_input();
 
-        let cards = parse_cards(test_grid);
+        let cards = parse_cards(&test_grid.join("\n")).unwrap();
         let total = cards.recursive_card_count();
 
         assert_eq!(30, total);
+        assert_eq!(Ok(30), cards.card_count_dp());
     }
 
     #[test]
-    fn test_recursive_card_count_full_input() {
-        let test_grid: Vec<String> = read_file_to_vec("input/day4-input");
+    fn test_recursive_card_count_from_file() {
+        let test_grid: Vec<String> = read_file_to_vec("examples/day4/example.txt");
 
-        let cards = parse_cards(test_grid);
+        let cards = parse_cards(&test_grid.join("\n")).unwrap();
         let total = cards.recursive_card_count();
 
-        assert_eq!(10425665, total);
+        assert_eq!(30, total);
+    }
+
+    #[test]
+    fn test_cards_kept_in_id_order() {
+        let mut test_grid: Vec<String> = create_test_input();
+        test_grid.reverse();
+
+        let cards = parse_cards(&test_grid.join("\n")).unwrap();
+        let ids: Vec<u32> = cards.iter().map(|card| card.id()).collect();
+
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], ids);
+    }
+
+    #[test]
+    fn test_report() {
+        let cards = parse_cards(&create_test_input().join("\n")).unwrap();
+        let report = cards.report();
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert_eq!(6, lines.len());
+        assert_eq!("Card 1 matched [83, 86, 17, 48] and scored 8", lines[0]);
+        assert_eq!("Card 6 matched [] and scored 0", lines[5]);
+    }
+
+    #[test]
+    fn test_duplicate_card() {
+        let mut test_grid: Vec<String> = create_test_input();
+        test_grid.push(String::from("Card 2: 1 2 3 | 4 5 6"));
+
+        assert_eq!(
+            CardError::Duplicate(2),
+            parse_cards(&test_grid.join("\n")).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_missing_card() {
+        let mut test_grid: Vec<String> = create_test_input();
+        test_grid.remove(2);
+
+        assert_eq!(
+            CardError::Missing(3),
+            parse_cards(&test_grid.join("\n")).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_malformed_card() {
+        let test_grid = vec![
+            String::from("Card 1: 1 2 3 | 4 5 6"),
+            String::from("Card two: 1 2 3 | 4 5 6"),
+        ];
+
+        assert_eq!(
+            CardError::Malformed(ParseError {
+                line: 2,
+                column: 6,
+                text: String::from("Card two: 1 2 3 | 4 5 6"),
+                expected: String::from("a number"),
+            }),
+            parse_cards(&test_grid.join("\n")).unwrap_err()
+        );
     }
 
     fn create_test_input() -> Vec<String> {
-        vec![
-            String::from("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"),
-            String::from("Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19"),
-            String::from("Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1"),
-            String::from("Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83"),
-            String::from("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36"),
-            String::from("Card     6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"),
-        ]
+        split_lines(&example(4, "example"))
     }
 }
@@ -1,233 +1,128 @@
 use std::{i64::MAX, time::Instant};
 
 use nom::{
-    bytes::complete::tag,
-    bytes::complete::take,
     bytes::complete::take_while1,
-    character::complete::{digit1, multispace0, multispace1},
-    combinator::{map, map_res},
-    error::Error,
-    multi::separated_list0,
-    number,
-    sequence::pair,
-    sequence::tuple,
-    IResult,
+    character::complete::{line_ending, space0, space1},
+    combinator::map,
+    sequence::{preceded, tuple},
 };
 
-use crate::common::file_io::read_file_to_string;
-
-fn seed_trace(
-    seed: i64,
-    seed_soil_map: &Vec<AlmanacMap>,
-    soil_fert_map: &Vec<AlmanacMap>,
-    fert_water_map: &Vec<AlmanacMap>,
-    water_light_map: &Vec<AlmanacMap>,
-    light_temperature_map: &Vec<AlmanacMap>,
-    temp_humid_map: &Vec<AlmanacMap>,
-    humid_loc_map: &Vec<AlmanacMap>,
-) -> (i64, i64) {
-    {
-        let now = Instant::now();
-        let mut soil = seed;
-        for map in seed_soil_map {
-            if seed >= map.source_range_start && seed <= map.source_range_start + map.range_length {
-                soil = (seed - map.source_range_start) + map.destination_range_start;
-                break;
-            }
-        }
-
-        let mut fert = soil;
-        for map in soil_fert_map {
-            if soil >= map.source_range_start && soil <= map.source_range_start + map.range_length {
-                fert = (soil - map.source_range_start) + map.destination_range_start;
-                break;
-            }
-        }
-
-        let mut water = fert;
-        for map in fert_water_map {
-            if fert >= map.source_range_start && fert <= map.source_range_start + map.range_length {
-                water = (fert - map.source_range_start) + map.destination_range_start;
-                break;
-            }
-        }
-
-        let mut light = water;
-        for map in water_light_map {
-            if water >= map.source_range_start && water <= map.source_range_start + map.range_length
-            {
-                light = (water - map.source_range_start) + map.destination_range_start;
-                break;
-            }
-        }
-
-        let mut temperature = light;
-        for map in light_temperature_map {
-            if light >= map.source_range_start && light <= map.source_range_start + map.range_length
-            {
-                temperature = (light - map.source_range_start) + map.destination_range_start;
-                break;
-            }
-        }
+use crate::{
+    common::{
+        answer::{Answer, Overflow, Solved, Unsolved},
+        file_io::read_file_to_string,
+        parse::{
+            blank_line, blocks, labelled, lines, literal, number, numbers, parse_all, Expected,
+            PResult, ParseError,
+        },
+        progress::{Progress, Stopped},
+    },
+    debug,
+    runner::Solution,
+    trace,
+};
 
-        let mut humidity = temperature;
-        for map in temp_humid_map {
-            if temperature >= map.source_range_start
-                && temperature <= map.source_range_start + map.range_length
-            {
-                humidity = (temperature - map.source_range_start) + map.destination_range_start;
-                break;
-            }
-        }
+pub fn seed_trace(seed: i64, layers: &[Vec<AlmanacMap>]) -> (i64, i64) {
+    let now = Instant::now();
 
-        let mut location = humidity;
-        for map in humid_loc_map {
-            if humidity >= map.source_range_start
-                && humidity <= map.source_range_start + map.range_length
-            {
-                location = (humidity - map.source_range_start) + map.destination_range_start;
-                break;
-            }
+    // map the value through each layer in turn, seed to soil through to humidity to location
+    let mut value = seed;
+    for layer in layers {
+        if let Some(mapped) = layer.iter().find_map(|map| map.convert(value)) {
+            value = mapped;
         }
-
-        let elapsed_time = now.elapsed();
-        println!(
-            "Running slow_function() took {} nanos.",
-            elapsed_time.as_nanos()
-        );
-
-        (seed, location)
     }
-}
 
-fn location_traceseed(
-    location: i64,
-    seed_soil_map: &Vec<AlmanacMap>,
-    soil_fert_map: &Vec<AlmanacMap>,
-    fert_water_map: &Vec<AlmanacMap>,
-    water_light_map: &Vec<AlmanacMap>,
-    light_temperature_map: &Vec<AlmanacMap>,
-    temp_humid_map: &Vec<AlmanacMap>,
-    humid_loc_map: &Vec<AlmanacMap>,
-) -> (i64, i64) {
-    let mut humid = location;
-    for map in humid_loc_map {
-        if location >= map.destination_range_start
-            && location < map.destination_range_start + map.range_length
-        {
-            humid = (location - map.destination_range_start) + map.source_range_start;
-            break;
-        }
-    }
+    trace!(
+        "seed {seed} traced to {value} in {} nanos",
+        now.elapsed().as_nanos()
+    );
 
-    let mut temp = humid;
-    for map in temp_humid_map {
-        if humid >= map.destination_range_start
-            && humid < map.destination_range_start + map.range_length
-        {
-            temp = (humid - map.destination_range_start) + map.source_range_start;
-            break;
-        }
-    }
-
-    let mut light = temp;
-    for map in light_temperature_map {
-        if temp >= map.destination_range_start
-            && temp < map.destination_range_start + map.range_length
-        {
-            light = (temp - map.destination_range_start) + map.source_range_start;
-            break;
-        }
-    }
+    (seed, value)
+}
 
-    let mut water = light;
-    for map in water_light_map {
-        if light >= map.destination_range_start
-            && light < map.destination_range_start + map.range_length
-        {
-            water = (light - map.destination_range_start) + map.source_range_start;
-            break;
+/// Push whole seed ranges, given as `(start, end)` with `end` exclusive, through the layers,
+/// splitting them wherever a map starts or ends, and return the lowest location reached. Within
+/// a layer the first map covering a value wins, the same as `seed_trace`.
+pub fn lowest_location_for_ranges(
+    seed_ranges: &[(i64, i64)],
+    layers: &[Vec<AlmanacMap>],
+) -> Option<i64> {
+    let mut ranges = seed_ranges.to_vec();
+
+    for layer in layers {
+        let mut mapped = Vec::new();
+        let mut pending = ranges;
+
+        for map in layer {
+            let source_end = map.source_range_start + map.range_length;
+            let offset = map.destination_range_start - map.source_range_start;
+            let mut unmatched = Vec::new();
+
+            for (start, end) in pending {
+                // the piece before the map, the piece it covers, and the piece after it
+                let before = (start, end.min(map.source_range_start));
+                let inside = (start.max(map.source_range_start), end.min(source_end));
+                let after = (start.max(source_end), end);
+
+                if before.0 < before.1 {
+                    unmatched.push(before);
+                }
+                if inside.0 < inside.1 {
+                    mapped.push((inside.0 + offset, inside.1 + offset));
+                }
+                if after.0 < after.1 {
+                    unmatched.push(after);
+                }
+            }
+            pending = unmatched;
         }
-    }
 
-    let mut fert = water;
-    for map in fert_water_map {
-        if water >= map.destination_range_start
-            && water < map.destination_range_start + map.range_length
-        {
-            fert = (water - map.destination_range_start) + map.source_range_start;
-            break;
-        }
+        // anything no map covered keeps its value
+        mapped.extend(pending);
+        ranges = mapped;
     }
 
-    let mut soil = fert;
-    for map in soil_fert_map {
-        if fert >= map.destination_range_start
-            && fert < map.destination_range_start + map.range_length
-        {
-            soil = (fert - map.destination_range_start) + map.source_range_start;
-            break;
-        }
-    }
+    ranges.iter().map(|(start, _)| *start).min()
+}
 
-    let mut seed = soil;
-    for map in seed_soil_map {
-        if soil >= map.destination_range_start
-            && soil < map.destination_range_start + map.range_length
-        {
-            seed = (soil - map.destination_range_start) + map.source_range_start;
-            break;
+fn location_traceseed(location: i64, layers: &[Vec<AlmanacMap>]) -> (i64, i64) {
+    // walk the layers backwards, location to humidity through to soil to seed
+    let mut value = location;
+    for layer in layers.iter().rev() {
+        for map in layer {
+            if value >= map.destination_range_start
+                && value < map.destination_range_start + map.range_length
+            {
+                value = (value - map.destination_range_start) + map.source_range_start;
+                break;
+            }
         }
     }
 
-    (seed, location)
+    (value, location)
 }
 
 pub fn puz1() {
     let input = read_file_to_string("input/day5-input");
 
-    let (input, seed_list) = Almanac::seeds(&input).unwrap();
-    let (input, seed_soil_map) = Almanac::seed_to_soil(&input).unwrap();
-    let (input, soil_fert_map) = Almanac::soil_to_fertilizer(&input).unwrap();
-    let (input, fert_water_map) = Almanac::fertilizer_to_water(&input).unwrap();
-    let (input, water_light_map) = Almanac::water_to_light(&input).unwrap();
-    let (input, light_temperature_map) = Almanac::light_to_temperature(&input).unwrap();
-    let (input, temp_humid_map) = Almanac::temperature_to_humidity(&input).unwrap();
-    let (input, humid_loc_map) = Almanac::humidity_to_location(&input).unwrap();
-
-    println!("{seed_list:?}");
-    println!("{seed_soil_map:?}");
-    println!("{soil_fert_map:?}");
-    println!("{fert_water_map:?}");
-    println!("{water_light_map:?}");
-    println!("{light_temperature_map:?}");
-    println!("{temp_humid_map:?}");
-    println!("{humid_loc_map:?}");
-    println!("{input:?}");
-
-    let seed_location_list: Vec<(i64, i64)> = seed_list
+    let Almanac { seeds, layers } =
+        parse(&input).unwrap_or_else(|e| panic!("failed to parse almanac: {e}"));
+
+    debug!("seeds {seeds:?}");
+    debug!("layers {layers:?}");
+
+    let seed_location_list: Vec<(i64, i64)> = seeds
         .iter()
-        .map(|s| s.parse().expect("error parsing seed number"))
-        .map(|seed| {
-            seed_trace(
-                seed,
-                &seed_soil_map,
-                &soil_fert_map,
-                &fert_water_map,
-                &water_light_map,
-                &light_temperature_map,
-                &temp_humid_map,
-                &humid_loc_map,
-            )
-        })
+        .map(|seed| seed_trace(*seed, &layers))
         .collect();
 
-    println!("{seed_location_list:?}");
+    debug!("seed locations {seed_location_list:?}");
 
     let mut min_loc: i64 = MAX;
     let mut min_seed: (i64, i64) = (0, 0);
     for (seed, location) in seed_location_list {
-        if (location < min_loc) {
+        if location < min_loc {
             min_loc = location;
             min_seed = (seed, location);
         }
@@ -238,411 +133,353 @@ pub fn puz1() {
 
 pub fn puz2() {
     let input = read_file_to_string("input/day5-input");
-    reverse_brute_force(&input);
+    match reverse_brute_force(&input, &mut Progress::new("day5 reverse search")) {
+        Ok(seed) => println!("Found lowest seed location map: {seed:?}"),
+        Err(stopped) => println!("Reverse search {stopped}"),
+    }
+}
+
+pub fn parse(input: &str) -> Result<Almanac, ParseError> {
+    Almanac::parse(input)
+}
+
+pub fn part1(almanac: &Almanac) -> Solved {
+    let lowest = almanac
+        .seeds
+        .iter()
+        .map(|seed| seed_trace(*seed, &almanac.layers).1)
+        .min()
+        .ok_or_else(|| Unsolved::NoAnswer(String::from("the almanac has no seeds")))?;
+    Ok(Answer::from(lowest))
+}
+
+pub fn part2(almanac: &Almanac) -> Solved {
+    // seeds come in pairs of range start and length
+    if !almanac.seeds.len().is_multiple_of(2) {
+        return Err(Unsolved::NoAnswer(String::from(
+            "the seeds don't pair up into ranges",
+        )));
+    }
+    let seed_ranges = almanac
+        .seeds
+        .chunks(2)
+        .map(|pair| {
+            let end = pair[0]
+                .checked_add(pair[1])
+                .ok_or_else(Overflow::of::<i64>)?;
+            Ok((pair[0], end))
+        })
+        .collect::<Result<Vec<(i64, i64)>, Overflow>>()?;
+
+    let lowest = lowest_location_for_ranges(&seed_ranges, &almanac.layers)
+        .ok_or_else(|| Unsolved::NoAnswer(String::from("the seed ranges are all empty")))?;
+    Ok(Answer::from(lowest))
+}
+
+pub fn solution() -> Solution {
+    Solution::new(5, parse, part1, part2)
 }
 
-fn reverse_brute_force(input: &str) -> (i64, i64) {
-    let (input, seed_list) = Almanac::seeds(&input).unwrap();
-    let (input, seed_soil_map) = Almanac::seed_to_soil(&input).unwrap();
-    let (input, soil_fert_map) = Almanac::soil_to_fertilizer(&input).unwrap();
-    let (input, fert_water_map) = Almanac::fertilizer_to_water(&input).unwrap();
-    let (input, water_light_map) = Almanac::water_to_light(&input).unwrap();
-    let (input, light_temperature_map) = Almanac::light_to_temperature(&input).unwrap();
-    let (input, temp_humid_map) = Almanac::temperature_to_humidity(&input).unwrap();
-    let (input, humid_loc_map) = Almanac::humidity_to_location(&input).unwrap();
-
-    let mut seed_itr = seed_list.iter();
+/// Walk locations upwards from zero, tracing each back to a seed, until one lands in a seed
+/// range. Slow, so it reports through `progress` and gives up when that says to.
+fn reverse_brute_force(input: &str, progress: &mut Progress) -> Result<(i64, i64), Stopped> {
+    let Almanac { seeds, layers } =
+        parse(input).unwrap_or_else(|e| panic!("failed to parse almanac: {e}"));
+
+    let mut seed_itr = seeds.iter();
     let mut next_seed = seed_itr.next();
     let mut seed_targets: Vec<(i64, i64)> = Vec::new();
-    while next_seed != None {
-        let start: i64 = next_seed.unwrap().parse().expect("failed to parse seed");
+    while next_seed.is_some() {
+        let start: i64 = *next_seed.unwrap();
         next_seed = seed_itr.next();
-        let increment: i64 = next_seed.unwrap().parse().expect("failed to parse seed");
+        let increment: i64 = *next_seed.unwrap();
 
         seed_targets.push((start, start + increment));
         next_seed = seed_itr.next();
     }
 
-    println!("{seed_targets:?}");
+    debug!("seed ranges {seed_targets:?}");
+
+    // no location can be higher than every seed and every map destination
+    let highest = seed_targets
+        .iter()
+        .map(|(_, end)| *end)
+        .chain(
+            layers
+                .iter()
+                .flatten()
+                .map(|map| map.destination_range_start + map.range_length),
+        )
+        .max()
+        .unwrap_or(0);
+    progress.set_total(highest as u64);
 
     let mut location_start: i64 = 0;
-    let mut seed = (MAX, MAX);
-    'outer: loop {
-        seed = location_traceseed(
-            location_start,
-            &seed_soil_map,
-            &soil_fert_map,
-            &fert_water_map,
-            &water_light_map,
-            &light_temperature_map,
-            &temp_humid_map,
-            &humid_loc_map,
-        );
+    let seed = 'outer: loop {
+        let seed = location_traceseed(location_start, &layers);
 
         for seed_range in &seed_targets {
             if seed.0 >= seed_range.0 && seed.0 < seed_range.1 {
                 // found a seed in range
-                break 'outer;
+                break 'outer seed;
             }
         }
 
-        location_start = location_start + 1;
-    }
+        location_start += 1;
+        progress.step()?;
+    };
 
-    println!("Found lowest seed location map: {seed:?}");
-    seed
+    Ok(seed)
 }
 
 #[derive(Debug)]
-struct AlmanacMap {
-    destination_range_start: i64,
-    source_range_start: i64,
-    range_length: i64,
+pub struct AlmanacMap {
+    pub destination_range_start: i64,
+    pub source_range_start: i64,
+    pub range_length: i64,
 }
 
-struct Almanac {}
-
-impl Almanac {
-    pub fn seeds(i: &str) -> IResult<&str, Vec<&str>> {
-        let seed_parser = pair(tag("seeds: "), separated_list0(multispace0, digit1));
-
-        map(seed_parser, |(_, seed_ids)| Vec::from(seed_ids))(i)
+impl AlmanacMap {
+    /// Where this map sends `value`, or `None` if it's outside the source range.
+    pub fn convert(&self, value: i64) -> Option<i64> {
+        (value >= self.source_range_start && value < self.source_range_start + self.range_length)
+            .then(|| value - self.source_range_start + self.destination_range_start)
     }
+}
 
-    pub fn seed_to_soil(i: &str) -> IResult<&str, Vec<AlmanacMap>> {
-        let seed_soil_parser = pair(
-            tag::<&str, &str, Error<_>>("\n\nseed-to-soil map:\n"),
-            separated_list0(
-                tag("\n"),
-                tuple((digit1, multispace1, digit1, multispace1, digit1)),
-            ),
-        );
+/// The seed numbers as listed and every layer of maps in order.
+pub struct Almanac {
+    pub seeds: Vec<i64>,
+    pub layers: Vec<Vec<AlmanacMap>>,
+}
 
-        let maps: Result<(&str, Vec<AlmanacMap>), nom::Err<_>> =
-            map(seed_soil_parser, |(_, maps)| {
-                maps.iter()
-                    .map(
-                        |(d_range_start, _, s_range_start, _, range_length)| AlmanacMap {
-                            destination_range_start: d_range_start.parse().expect(""),
-                            source_range_start: s_range_start.parse().expect(""),
-                            range_length: range_length.parse().expect(""),
-                        },
-                    )
-                    .collect()
-            })(i);
-        maps
+impl Almanac {
+    pub fn parse(input: &str) -> Result<Almanac, ParseError> {
+        parse_all(input, |input| {
+            let (input, seeds) = labelled("seeds", numbers)(input)?;
+            let (input, layers) = preceded(blank_line, Almanac::layers)(input)?;
+            Ok((input, Almanac { seeds, layers }))
+        })
     }
 
-    pub fn soil_to_fertilizer(i: &str) -> IResult<&str, Vec<AlmanacMap>> {
-        let seed_soil_parser = pair(
-            tag::<&str, &str, Error<_>>("\n\nsoil-to-fertilizer map:\n"),
-            separated_list0(
-                tag("\n"),
-                tuple((digit1, multispace1, digit1, multispace1, digit1)),
-            ),
-        );
-
-        let maps: Result<(&str, Vec<AlmanacMap>), nom::Err<_>> =
-            map(seed_soil_parser, |(_, maps)| {
-                maps.iter()
-                    .map(
-                        |(d_range_start, _, s_range_start, _, range_length)| AlmanacMap {
-                            destination_range_start: d_range_start.parse().expect(""),
-                            source_range_start: s_range_start.parse().expect(""),
-                            range_length: range_length.parse().expect(""),
-                        },
-                    )
-                    .collect()
-            })(i);
-        maps
+    /// One `<from>-to-<to> map:` block. The name isn't checked so almanacs can have any number
+    /// of layers, they're applied in the order they appear.
+    pub fn layer(i: &str) -> PResult<'_, Vec<AlmanacMap>> {
+        let header = tuple((
+            take_while1(|c: char| c != ' ' && c != '\n'),
+            literal(" map:"),
+            line_ending,
+        ));
+        preceded(header, lines(Almanac::map))(i)
     }
 
-    pub fn fertilizer_to_water(i: &str) -> IResult<&str, Vec<AlmanacMap>> {
-        let seed_soil_parser = pair(
-            tag::<&str, &str, Error<_>>("\n\nfertilizer-to-water map:\n"),
-            separated_list0(
-                tag("\n"),
-                tuple((digit1, multispace1, digit1, multispace1, digit1)),
-            ),
-        );
+    /// `<destination> <source> <length>`. Both ranges have to start at zero or above and end
+    /// within an `i64`, so mapping a value through them can't overflow.
+    fn map(i: &str) -> PResult<'_, AlmanacMap> {
+        let (rest, almanac_map) = map(
+            tuple((number, space1, number, space1, number, space0)),
+            |(destination_range_start, _, source_range_start, _, range_length, _)| AlmanacMap {
+                destination_range_start,
+                source_range_start,
+                range_length,
+            },
+        )(i)?;
+
+        let fits = |start: i64| start >= 0 && start.checked_add(almanac_map.range_length).is_some();
+        if almanac_map.range_length < 0
+            || !fits(almanac_map.destination_range_start)
+            || !fits(almanac_map.source_range_start)
+        {
+            return Err(nom::Err::Failure(Expected::new(
+                i,
+                "ranges that start at zero or above and end within i64",
+            )));
+        }
 
-        let maps: Result<(&str, Vec<AlmanacMap>), nom::Err<_>> =
-            map(seed_soil_parser, |(_, maps)| {
-                maps.iter()
-                    .map(
-                        |(d_range_start, _, s_range_start, _, range_length)| AlmanacMap {
-                            destination_range_start: d_range_start.parse().expect(""),
-                            source_range_start: s_range_start.parse().expect(""),
-                            range_length: range_length.parse().expect(""),
-                        },
-                    )
-                    .collect()
-            })(i);
-        maps
+        Ok((rest, almanac_map))
     }
 
-    pub fn water_to_light(i: &str) -> IResult<&str, Vec<AlmanacMap>> {
-        let seed_soil_parser = pair(
-            tag::<&str, &str, Error<_>>("\n\nwater-to-light map:\n"),
-            separated_list0(
-                tag("\n"),
-                tuple((digit1, multispace1, digit1, multispace1, digit1)),
-            ),
-        );
-
-        let maps: Result<(&str, Vec<AlmanacMap>), nom::Err<_>> =
-            map(seed_soil_parser, |(_, maps)| {
-                maps.iter()
-                    .map(
-                        |(d_range_start, _, s_range_start, _, range_length)| AlmanacMap {
-                            destination_range_start: d_range_start.parse().expect(""),
-                            source_range_start: s_range_start.parse().expect(""),
-                            range_length: range_length.parse().expect(""),
-                        },
-                    )
-                    .collect()
-            })(i);
-        maps
+    pub fn layers(i: &str) -> PResult<'_, Vec<Vec<AlmanacMap>>> {
+        blocks(Almanac::layer)(i)
     }
+}
 
-    pub fn light_to_temperature(i: &str) -> IResult<&str, Vec<AlmanacMap>> {
-        let seed_soil_parser = pair(
-            tag::<&str, &str, Error<_>>("\n\nlight-to-temperature map:\n"),
-            separated_list0(
-                tag("\n"),
-                tuple((digit1, multispace1, digit1, multispace1, digit1)),
-            ),
-        );
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{
+            differential::{check, smaller, without_each, Shrink},
+            progress::Progress,
+        },
+        day5::{lowest_location_for_ranges, reverse_brute_force, seed_trace, Almanac, AlmanacMap},
+        runner::example,
+    };
+
+    /// Seed ranges as `(start, length)` and layers of `(destination, source, length)` maps.
+    #[derive(Clone, Debug)]
+    struct SmallAlmanac {
+        seeds: Vec<(i64, i64)>,
+        layers: Vec<Vec<(i64, i64, i64)>>,
+    }
 
-        let maps: Result<(&str, Vec<AlmanacMap>), nom::Err<_>> =
-            map(seed_soil_parser, |(_, maps)| {
-                maps.iter()
-                    .map(
-                        |(d_range_start, _, s_range_start, _, range_length)| AlmanacMap {
-                            destination_range_start: d_range_start.parse().expect(""),
-                            source_range_start: s_range_start.parse().expect(""),
-                            range_length: range_length.parse().expect(""),
-                        },
-                    )
-                    .collect()
-            })(i);
-        maps
+    impl SmallAlmanac {
+        fn layers(&self) -> Vec<Vec<AlmanacMap>> {
+            self.layers
+                .iter()
+                .map(|layer| {
+                    layer
+                        .iter()
+                        .map(|(destination, source, length)| AlmanacMap {
+                            destination_range_start: *destination,
+                            source_range_start: *source,
+                            range_length: *length,
+                        })
+                        .collect()
+                })
+                .collect()
+        }
     }
 
-    pub fn temperature_to_humidity(i: &str) -> IResult<&str, Vec<AlmanacMap>> {
-        let seed_soil_parser = pair(
-            tag::<&str, &str, Error<_>>("\n\ntemperature-to-humidity map:\n"),
-            separated_list0(
-                tag("\n"),
-                tuple((digit1, multispace1, digit1, multispace1, digit1)),
-            ),
-        );
+    impl Shrink for SmallAlmanac {
+        fn shrink(&self) -> Vec<SmallAlmanac> {
+            let mut candidates = Vec::new();
+
+            // keep at least one seed range so there's always an answer
+            if self.seeds.len() > 1 {
+                for seeds in without_each(&self.seeds) {
+                    candidates.push(SmallAlmanac {
+                        seeds,
+                        layers: self.layers.clone(),
+                    });
+                }
+            }
+            for layers in without_each(&self.layers) {
+                candidates.push(SmallAlmanac {
+                    seeds: self.seeds.clone(),
+                    layers,
+                });
+            }
+            for (index, layer) in self.layers.iter().enumerate() {
+                for maps in without_each(layer) {
+                    let mut layers = self.layers.clone();
+                    layers[index] = maps;
+                    candidates.push(SmallAlmanac {
+                        seeds: self.seeds.clone(),
+                        layers,
+                    });
+                }
+            }
+            for (index, (start, length)) in self.seeds.iter().enumerate() {
+                for start in smaller(*start as u64, 0) {
+                    let mut seeds = self.seeds.clone();
+                    seeds[index].0 = start as i64;
+                    candidates.push(SmallAlmanac {
+                        seeds,
+                        layers: self.layers.clone(),
+                    });
+                }
+                for length in smaller(*length as u64, 1) {
+                    let mut seeds = self.seeds.clone();
+                    seeds[index].1 = length as i64;
+                    candidates.push(SmallAlmanac {
+                        seeds,
+                        layers: self.layers.clone(),
+                    });
+                }
+            }
 
-        let maps: Result<(&str, Vec<AlmanacMap>), nom::Err<_>> =
-            map(seed_soil_parser, |(_, maps)| {
-                maps.iter()
-                    .map(
-                        |(d_range_start, _, s_range_start, _, range_length)| AlmanacMap {
-                            destination_range_start: d_range_start.parse().expect(""),
-                            source_range_start: s_range_start.parse().expect(""),
-                            range_length: range_length.parse().expect(""),
-                        },
-                    )
-                    .collect()
-            })(i);
-        maps
+            candidates
+        }
     }
 
-    pub fn humidity_to_location(i: &str) -> IResult<&str, Vec<AlmanacMap>> {
-        let seed_soil_parser = pair(
-            tag::<&str, &str, Error<_>>("\n\nhumidity-to-location map:\n"),
-            separated_list0(
-                tag("\n"),
-                tuple((digit1, multispace1, digit1, multispace1, digit1)),
-            ),
+    #[test]
+    fn test_range_mapping_matches_seed_trace() {
+        check(
+            "day5 seed_trace vs range mapping",
+            300,
+            |rng| SmallAlmanac {
+                seeds: (0..rng.between(1, 3))
+                    .map(|_| (rng.below(40) as i64, rng.between(1, 15) as i64))
+                    .collect(),
+                layers: (0..rng.between(1, 4))
+                    .map(|_| {
+                        (0..rng.below(4))
+                            .map(|_| {
+                                (
+                                    rng.below(50) as i64,
+                                    rng.below(50) as i64,
+                                    rng.between(1, 15) as i64,
+                                )
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            },
+            |almanac| {
+                let layers = almanac.layers();
+
+                let traced = almanac
+                    .seeds
+                    .iter()
+                    .flat_map(|(start, length)| *start..start + length)
+                    .map(|seed| seed_trace(seed, &layers).1)
+                    .min();
+                let ranges: Vec<(i64, i64)> = almanac
+                    .seeds
+                    .iter()
+                    .map(|(start, length)| (*start, start + length))
+                    .collect();
+                let mapped = lowest_location_for_ranges(&ranges, &layers);
+
+                if traced == mapped {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "seed_trace found {traced:?}, range mapping found {mapped:?}"
+                    ))
+                }
+            },
         );
-
-        let maps: Result<(&str, Vec<AlmanacMap>), nom::Err<_>> =
-            map(seed_soil_parser, |(_, maps)| {
-                maps.iter()
-                    .map(
-                        |(d_range_start, _, s_range_start, _, range_length)| AlmanacMap {
-                            destination_range_start: d_range_start.parse().expect(""),
-                            source_range_start: s_range_start.parse().expect(""),
-                            range_length: range_length.parse().expect(""),
-                        },
-                    )
-                    .collect()
-            })(i);
-        maps
     }
-}
-
-mod tests {
-    use std::i64::MAX;
-
-    use crate::day5::Almanac;
-
-    use super::reverse_brute_force;
 
     #[test]
     fn test_total_points() {
         let input = create_test_input();
 
-        let (input, seed_list) = Almanac::seeds(&input).unwrap();
-        let (input, seed_soil_map) = Almanac::seed_to_soil(&input).unwrap();
-        let (input, soil_fert_map) = Almanac::soil_to_fertilizer(&input).unwrap();
-        let (input, fert_water_map) = Almanac::fertilizer_to_water(&input).unwrap();
-        let (input, water_light_map) = Almanac::water_to_light(&input).unwrap();
-        let (input, light_temperature_map) = Almanac::light_to_temperature(&input).unwrap();
-        let (input, temp_humid_map) = Almanac::temperature_to_humidity(&input).unwrap();
-        let (input, humid_loc_map) = Almanac::humidity_to_location(&input).unwrap();
-
-        println!("{seed_list:?}");
-        println!("{seed_soil_map:?}");
-        println!("{soil_fert_map:?}");
-        println!("{fert_water_map:?}");
-        println!("{water_light_map:?}");
-        println!("{light_temperature_map:?}");
-        println!("{temp_humid_map:?}");
-        println!("{humid_loc_map:?}");
-        println!("{input:?}");
-
-        let seed_location_list: Vec<(i64, i64)> = seed_list
-            .iter()
-            .map(|s| s.parse().expect("error parsing seed number"))
-            .map(|seed: i64| {
-                let mut soil = seed;
-                for map in &seed_soil_map {
-                    if seed >= map.source_range_start
-                        && seed <= map.source_range_start + map.range_length
-                    {
-                        soil = (seed - map.source_range_start) + map.destination_range_start;
-                        break;
-                    }
-                }
-
-                let mut fert = soil;
-                for map in &soil_fert_map {
-                    if soil >= map.source_range_start
-                        && soil <= map.source_range_start + map.range_length
-                    {
-                        fert = (soil - map.source_range_start) + map.destination_range_start;
-                        break;
-                    }
-                }
+        let Almanac { seeds, layers } = Almanac::parse(&input).unwrap();
 
-                let mut water = fert;
-                for map in &fert_water_map {
-                    if fert >= map.source_range_start
-                        && fert <= map.source_range_start + map.range_length
-                    {
-                        water = (fert - map.source_range_start) + map.destination_range_start;
-                        break;
-                    }
-                }
+        assert_eq!(7, layers.len());
 
-                let mut light = water;
-                for map in &water_light_map {
-                    if water >= map.source_range_start
-                        && water <= map.source_range_start + map.range_length
-                    {
-                        light = (water - map.source_range_start) + map.destination_range_start;
-                        break;
-                    }
-                }
-
-                let mut temperature = light;
-                for map in &light_temperature_map {
-                    if light >= map.source_range_start
-                        && light <= map.source_range_start + map.range_length
-                    {
-                        temperature =
-                            (light - map.source_range_start) + map.destination_range_start;
-                        break;
-                    }
-                }
-
-                let mut humidity = temperature;
-                for map in &temp_humid_map {
-                    if temperature >= map.source_range_start
-                        && temperature <= map.source_range_start + map.range_length
-                    {
-                        humidity =
-                            (temperature - map.source_range_start) + map.destination_range_start;
-                        break;
-                    }
-                }
-
-                let mut location = humidity;
-                for map in &humid_loc_map {
-                    if humidity >= map.source_range_start
-                        && humidity <= map.source_range_start + map.range_length
-                    {
-                        location =
-                            (humidity - map.source_range_start) + map.destination_range_start;
-                        break;
-                    }
-                }
-
-                (seed, location)
-            })
+        let seed_location_list: Vec<(i64, i64)> = seeds
+            .iter()
+            .map(|seed| seed_trace(*seed, &layers))
             .collect();
 
-        println!("{seed_location_list:?}");
+        assert_eq!(
+            vec![(79, 82), (14, 43), (55, 86), (13, 35)],
+            seed_location_list
+        );
+    }
 
-        let mut min_loc: i64 = MAX;
-        let mut min_seed: (i64, i64) = (0, 0);
-        for (seed, location) in seed_location_list {
-            if (location < min_loc) {
-                min_loc = location;
-                min_seed = (seed, location);
-            }
-        }
+    #[test]
+    fn test_any_number_of_layers() {
+        let input = "seeds: 1 10\n\na-to-b map:\n5 0 5\n\nb-to-c map:\n0 5 3";
+
+        let Almanac { seeds, layers } = Almanac::parse(input).unwrap();
 
-        println!("{min_seed:?}");
+        assert_eq!(vec![1, 10], seeds);
+        assert_eq!(2, layers.len());
+        assert_eq!((1, 1), seed_trace(1, &layers));
     }
 
     #[test]
     fn test_reverse_brute() {
         let input = create_test_input();
-        let seed = reverse_brute_force(&input);
+        let seed = reverse_brute_force(&input, &mut Progress::new("test")).unwrap();
+        assert_eq!(46, seed.1);
     }
+
     fn create_test_input() -> String {
-        String::from(
-            r"seeds: 79 14 55 13
-
-seed-to-soil map:
-50 98 2
-52 50 48
-
-soil-to-fertilizer map:
-0 15 37
-37 52 2
-39 0 15
-
-fertilizer-to-water map:
-49 53 8
-0 11 42
-42 0 7
-57 7 4
-
-water-to-light map:
-88 18 7
-18 25 70
-
-light-to-temperature map:
-45 77 23
-81 45 19
-68 64 13
-
-temperature-to-humidity map:
-0 69 1
-1 0 69
-
-humidity-to-location map:
-60 56 37
-56 93 4",
-        )
+        example(5, "example")
     }
 }
@@ -1,19 +1,15 @@
-use nom::{
-    bytes::complete::tag,
-    bytes::complete::take,
-    bytes::complete::take_while1,
-    character::complete::{digit1, multispace0, multispace1},
-    combinator::{map, map_res},
-    error::Error,
-    multi::separated_list0,
-    number,
-    sequence::pair,
-    sequence::tuple,
-    IResult,
+use nom::{character::complete::line_ending, combinator::consumed, sequence::preceded};
+
+use crate::{
+    common::{
+        answer::{checked_product, Answer, Overflow, Solved},
+        file_io::read_file_to_string,
+        parse::{labelled, numbers, parse_all, ParseError},
+    },
+    debug,
+    runner::Solution,
 };
 
-use crate::common::file_io::read_file_to_string;
-
 pub fn puz1() {
     let input = read_file_to_string("input/day6-input");
     let answer = calc_ans1(&input);
@@ -26,47 +22,126 @@ pub fn puz2() {
     println!("multiply race win options: {answer}");
 }
 
+/// Every race as `(duration, record)`, plus the one race you get by ignoring the spaces, which
+/// is `None` when its numbers don't fit in a `u64`.
+pub struct Races {
+    pub races: Vec<(u64, u64)>,
+    pub kerned: Option<(u64, u64)>,
+}
+
+pub fn parse(input: &str) -> Result<Races, ParseError> {
+    parse_all(input, |input| {
+        let (rest, (time_row, times)) = consumed(labelled("Time", numbers))(input)?;
+        let (rest, (distance_row, distances)) =
+            preceded(line_ending, consumed(labelled("Distance", numbers)))(rest)?;
+
+        let races = times
+            .iter()
+            .copied()
+            .zip(distances.iter().copied())
+            .collect();
+        Ok((
+            rest,
+            Races {
+                races,
+                kerned: kern(time_row).zip(kern(distance_row)),
+            },
+        ))
+    })
+}
+
+/// The one number you get by reading a row of numbers without the spaces between them. The
+/// digits are taken as written so leading zeros keep their place.
+fn kern(row: &str) -> Option<u64> {
+    row.chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>()
+        .parse()
+        .ok()
+}
+
+pub fn part1(races: &Races) -> Solved {
+    let ways = races
+        .races
+        .iter()
+        .map(|(duration, record)| ways_to_win(*duration, *record));
+    Ok(Answer::from(checked_product(ways)?))
+}
+
+pub fn part2(races: &Races) -> Solved {
+    let (duration, record) = races.kerned.ok_or_else(Overflow::of::<u64>)?;
+    Ok(Answer::from(ways_to_win(duration, record)))
+}
+
+pub fn solution() -> Solution {
+    Solution::new(6, parse, part1, part2)
+}
+
 fn calc_ans2(input: &str) -> u64 {
-    let input = input.replace(" ", "");
-    let (input, time) = parse_time2(&input).unwrap();
-    let (input, distance) = parse_distance2(&input).unwrap();
+    let (duration, length_to_beat) = parse(input)
+        .unwrap_or_else(|e| panic!("failed to parse races: {e}"))
+        .kerned
+        .unwrap_or_else(|| panic!("puz2: {}", Overflow::of::<u64>()));
 
-    println!("{time:?}");
-    println!("{distance:?}");
+    debug!("time {duration}, distance {length_to_beat}");
 
-    let duration = time.parse().unwrap();
-    let length_to_beat: i64 = distance.parse().unwrap();
+    ways_to_win(duration, length_to_beat)
+}
 
-    let mut hold_time: i64 = 1;
+/// Count the hold times that beat `record` by trying every one of them.
+fn ways_to_win_by_counting(duration: u64, record: u64) -> u64 {
+    let mut hold_time = 1;
 
     let mut number_of_options = 0;
     while hold_time < duration {
         // for this hold time calculate distance travelled
         let distance = (duration - hold_time) * hold_time;
 
-        if distance > length_to_beat {
+        if distance > record {
             number_of_options += 1;
         }
         hold_time += 1;
     }
 
-    let answer = number_of_options;
-    answer
+    number_of_options
 }
 
-fn calc_ans1(input: &str) -> u32 {
-    let (input, times) = parse_time(input).unwrap();
-    let (input, distance) = parse_distance(input).unwrap();
-
-    let mut races: Vec<(u32, u32)> = Vec::new();
+/// Count the hold times that beat `record` without trying them all. The distance travelled,
+/// `(duration - hold) * hold`, is symmetric around `duration / 2` so once the shortest winning
+/// hold is known the rest follow. The quadratic formula, worked in integers, gives a first
+/// guess which is nudged to the exact value.
+pub fn ways_to_win(duration: u64, record: u64) -> u64 {
+    // the distance can be far beyond a u64 for long races
+    let beats = |hold: u64| (duration - hold) as u128 * hold as u128 > record as u128;
+    let half = duration / 2;
+
+    let discriminant = (duration as u128 * duration as u128).saturating_sub(4 * record as u128);
+    let mut shortest =
+        ((duration as u128 - discriminant.isqrt()) / 2).clamp(1, half.max(1) as u128) as u64;
+
+    while shortest > 1 && beats(shortest - 1) {
+        shortest -= 1;
+    }
+    while shortest <= half && !beats(shortest) {
+        shortest += 1;
+    }
 
-    let mut index = 0;
-    for time in times {
-        races.push((time.parse().unwrap(), distance[index].parse().unwrap()));
-        index += 1;
+    if shortest > half {
+        0
+    } else {
+        duration - 2 * shortest + 1
     }
+}
+
+fn calc_ans1(input: &str) -> u32 {
+    let races: Vec<(u32, u32)> = parse(input)
+        .unwrap_or_else(|e| panic!("failed to parse races: {e}"))
+        .races
+        .iter()
+        .map(|(duration, record)| (*duration as u32, *record as u32))
+        .collect();
 
-    println!("{races:?}");
+    debug!("races {races:?}");
 
     let mut multiply_race_win_options: u32 = 0;
 
@@ -74,68 +149,150 @@ fn calc_ans1(input: &str) -> u32 {
         let duration = race.0;
         let length_to_beat = race.1;
 
-        let mut hold_time = 1;
-
-        let mut winning_times: Vec<(u32, u32)> = Vec::new();
-        while hold_time < duration {
-            // for this hold time calculate distance travelled
-            let distance = (duration - hold_time) * hold_time;
-
-            if distance > length_to_beat {
-                winning_times.push((hold_time, distance));
-            }
-            hold_time += 1;
-        }
+        let winning_times = ways_to_win_by_counting(duration as u64, length_to_beat as u64) as u32;
 
         if multiply_race_win_options == 0 {
-            multiply_race_win_options = winning_times.len() as u32;
+            multiply_race_win_options = winning_times;
         } else {
-            multiply_race_win_options *= winning_times.len() as u32;
+            multiply_race_win_options *= winning_times;
         }
-        println!("found the following winning times {winning_times:?}");
+        debug!("found {winning_times} winning times for race {race:?}");
     }
 
     let answer = multiply_race_win_options;
     answer
 }
 
-fn parse_time2(input: &str) -> IResult<&str, &str> {
-    let time_parser = pair(tag("Time:"), digit1);
-
-    map(time_parser, |(_, times)| times)(input)
-}
-
-fn parse_distance2(input: &str) -> IResult<&str, &str> {
-    let time_parser = pair(tag("\nDistance:"), digit1);
-
-    map(time_parser, |(_, times)| times)(input)
-}
+#[cfg(test)]
+mod test {
+    use crate::{
+        common::answer::{Answer, Overflow, Unsolved},
+        common::differential::{check, smaller, Shrink},
+        day6::{calc_ans1, calc_ans2, parse, part1, part2, ways_to_win, ways_to_win_by_counting},
+        runner::example,
+    };
+
+    #[derive(Clone, Debug)]
+    struct Race {
+        duration: u64,
+        record: u64,
+    }
 
-fn parse_time(input: &str) -> IResult<&str, Vec<&str>> {
-    let time_parser = pair(
-        pair(tag("Time:"), multispace0),
-        separated_list0(multispace1, digit1),
-    );
+    impl Shrink for Race {
+        fn shrink(&self) -> Vec<Race> {
+            let mut candidates: Vec<Race> = smaller(self.duration, 0)
+                .into_iter()
+                .map(|duration| Race { duration, ..*self })
+                .collect();
+            candidates.extend(
+                smaller(self.record, 0)
+                    .into_iter()
+                    .map(|record| Race { record, ..*self }),
+            );
+            candidates
+        }
+    }
 
-    map(time_parser, |(_, times)| Vec::from(times))(input)
-}
+    #[test]
+    pub fn test_formula_matches_loop() {
+        check(
+            "day6 loop vs formula",
+            500,
+            |rng| {
+                let duration = rng.below(300);
+                // records either side of the best possible distance
+                let record = rng.below((duration * duration) / 4 + 10);
+                Race { duration, record }
+            },
+            |race| {
+                let counted = ways_to_win_by_counting(race.duration, race.record);
+                let formula = ways_to_win(race.duration, race.record);
+                if counted == formula {
+                    Ok(())
+                } else {
+                    Err(format!("loop counted {counted}, formula gave {formula}"))
+                }
+            },
+        );
+    }
 
-fn parse_distance(input: &str) -> IResult<&str, Vec<&str>> {
-    let time_parser = pair(
-        pair(tag("\nDistance:"), multispace1),
-        separated_list0(multispace1, digit1),
-    );
+    /// The same count as `ways_to_win`, found by a binary search for the shortest winning hold.
+    fn ways_to_win_by_search(duration: u64, record: u64) -> u64 {
+        let beats = |hold: u64| (duration - hold) as u128 * hold as u128 > record as u128;
+        let half = duration / 2;
+        if !beats(half) {
+            return 0;
+        }
+        // the shortest winning hold is above `low` and at most `high`
+        let (mut low, mut high) = (0, half);
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if beats(middle) {
+                high = middle;
+            } else {
+                low = middle;
+            }
+        }
+        duration - 2 * high + 1
+    }
 
-    map(time_parser, |(_, times)| Vec::from(times))(input)
-}
+    #[test]
+    pub fn test_formula_matches_search_for_long_races() {
+        check(
+            "day6 search vs formula",
+            500,
+            |rng| {
+                if rng.chance(25) {
+                    // far beyond 2^32, where any u64 record can be beaten
+                    let duration = rng.between(1 << 33, 1 << 40);
+                    Race {
+                        duration,
+                        record: rng.below(u64::MAX),
+                    }
+                } else {
+                    // near 2^32, with records at the edge of the best possible distance
+                    let duration = rng.between((1 << 32) - 1000, 1 << 33);
+                    let half = duration as u128 / 2;
+                    let best = half * (duration as u128 - half);
+                    let record = best
+                        .saturating_sub(rng.below(4) as u128)
+                        .min(u64::MAX as u128);
+                    Race {
+                        duration,
+                        record: record as u64,
+                    }
+                }
+            },
+            |race| {
+                let searched = ways_to_win_by_search(race.duration, race.record);
+                let formula = ways_to_win(race.duration, race.record);
+                if searched == formula {
+                    Ok(())
+                } else {
+                    Err(format!("search found {searched}, formula gave {formula}"))
+                }
+            },
+        );
+    }
 
-mod test {
-    use crate::day6::{calc_ans1, calc_ans2};
+    #[test]
+    pub fn test_one_winning_hold() {
+        let duration: u64 = 1 << 32;
+        let half = duration / 2;
+
+        assert_eq!(1, ways_to_win(duration, half * half - 1));
+        assert_eq!(2, ways_to_win(duration + 1, half * (half + 1) - 1));
+        assert_eq!(0, ways_to_win(duration, half * half));
+
+        // an odd duration whose square is too long for an f64 to hold exactly
+        let duration: u64 = 8037115907;
+        let half = duration / 2;
+        assert_eq!(2, ways_to_win(duration, half * (duration - half) - 1));
+    }
 
     #[test]
     pub fn test() {
-        let input = r"Time:      7  15   30
-Distance:  9  40  200";
+        let input = example(6, "example");
 
         let answer = calc_ans1(&input);
         assert_eq!(answer, 288);
@@ -143,10 +300,35 @@ Distance:  9  40  200";
 
     #[test]
     pub fn test2() {
-        let input = r"Time:      7  15   30
-Distance:  9  40  200";
+        let input = example(6, "example");
 
         let answer = calc_ans2(&input);
         assert_eq!(answer, 71503);
     }
+
+    #[test]
+    pub fn test_kern_keeps_leading_zeros() {
+        let races = parse("Time: 7 05\nDistance: 9 04").unwrap();
+
+        assert_eq!(vec![(7, 9), (5, 4)], races.races);
+        assert_eq!(Some((705, 904)), races.kerned);
+    }
+
+    #[test]
+    pub fn test_kern_overflow_fails_only_part2() {
+        // 21 races of 9ms, too many digits for the kerned race to fit in a u64
+        let races = parse(&format!(
+            "Time: {}\nDistance: {}",
+            ["9"; 21].join(" "),
+            ["1"; 21].join(" ")
+        ))
+        .unwrap();
+
+        assert_eq!(None, races.kerned);
+        assert_eq!(
+            Err(Unsolved::Overflow(Overflow::of::<u64>())),
+            part2(&races)
+        );
+        assert_eq!(Ok(Answer::from(8u64.pow(21))), part1(&races));
+    }
 }
@@ -3,7 +3,19 @@ use std::{
     collections::{HashMap, HashSet},
 };
 
-use crate::common::file_io::read_file_to_string;
+use nom::{
+    bytes::complete::take_while_m_n, character::complete::space1, error::context,
+    sequence::separated_pair,
+};
+
+use crate::{
+    common::{
+        answer::{Answer, Overflow, Solved},
+        file_io::read_file_to_string,
+        parse::{lines, number, parse_all, PResult, ParseError},
+    },
+    runner::Solution,
+};
 
 const FIVE_OF_A_KIND: u32 = 7;
 const FOUR_OF_A_KIND: u32 = 6;
@@ -15,8 +27,8 @@ const HIGH_CARD: u32 = 1;
 
 pub fn puz1() {
     let input = read_file_to_string("input/day7-input");
-    let cards = parse_cards(&input);
-    let answer = sort_and_score(cards);
+    let cards = parse_cards(&input).unwrap_or_else(|e| panic!("failed to parse hands: {e}"));
+    let answer = sort_and_score(cards).unwrap_or_else(|e| panic!("total winnings {e}"));
     println!("{answer}");
 }
 
@@ -24,19 +36,55 @@ pub fn puz2() {
     let mut input = read_file_to_string("input/day7-input");
     // replace all the jacks with * so we can use the same code for puz1 and puz2
     input = input.replace('J', "*");
-    let cards = parse_cards(&input);
-    let answer = sort_and_score(cards);
+    let cards = parse_cards(&input).unwrap_or_else(|e| panic!("failed to parse hands: {e}"));
+    let answer = sort_and_score(cards).unwrap_or_else(|e| panic!("total winnings {e}"));
     println!("{answer}");
 }
 
-fn sort_and_score(mut cards: Vec<Hand>) -> u32 {
+pub fn parse(input: &str) -> Result<Vec<Hand>, ParseError> {
+    parse_cards(input)
+}
+
+pub fn part1(hands: &[Hand]) -> Solved {
+    Ok(Answer::from(sort_and_score(hands.to_vec())?))
+}
+
+pub fn part2(hands: &[Hand]) -> Solved {
+    // jacks become jokers, the same swap puz2 makes
+    let hands = hands
+        .iter()
+        .map(|hand| {
+            let jokers: Vec<char> = hand
+                .hand
+                .iter()
+                .map(|card| if *card == 'J' { '*' } else { *card })
+                .collect();
+            Hand {
+                value: find_kind2(&jokers),
+                hand: jokers,
+                bid: hand.bid,
+            }
+        })
+        .collect();
+    Ok(Answer::from(sort_and_score(hands)?))
+}
+
+pub fn solution() -> Solution {
+    Solution::new(7, parse, |hands| part1(hands), |hands| part2(hands))
+}
+
+fn sort_and_score(mut cards: Vec<Hand>) -> Result<u32, Overflow> {
     cards.sort_by(|a, b| a.value.cmp(&b.value).then(a.compare_cards(b)));
 
-    let mut answer = 0;
-    for (index, card) in cards.iter().enumerate() {
-        answer += ((index as u32 + 1) * card.bid);
-    }
-    answer
+    cards
+        .iter()
+        .enumerate()
+        .try_fold(0_u32, |total, (index, card)| {
+            (index as u32 + 1)
+                .checked_mul(card.bid)
+                .and_then(|winnings| total.checked_add(winnings))
+                .ok_or_else(Overflow::of::<u32>)
+        })
 }
 
 fn card_value(card: char) -> u32 {
@@ -52,10 +100,10 @@ fn card_value(card: char) -> u32 {
     }
 }
 
-#[derive(Debug)]
-struct Hand {
-    hand: Vec<char>,
-    bid: u32,
+#[derive(Debug, Clone)]
+pub struct Hand {
+    pub hand: Vec<char>,
+    pub bid: u32,
     value: u32,
 }
 
@@ -115,28 +163,37 @@ fn find_kind2(input: &Vec<char>) -> u32 {
     }
 }
 
-fn parse_cards(input: &str) -> Vec<Hand> {
-    let lines: Vec<&str> = input.split("\n").map(|s| s.trim()).collect();
-    let mut hands = Vec::new();
-    for line in lines {
-        let split: Vec<&str> = line.split(" ").collect();
-
-        // calculate value
-        let hand = String::from(split[0]).chars().collect();
-        let kind = find_kind2(&hand);
-        hands.push(Hand {
-            hand: hand,
-            bid: split[1].parse().unwrap(),
-            value: kind,
-        });
-    }
-    hands
+fn parse_cards(input: &str) -> Result<Vec<Hand>, ParseError> {
+    parse_all(input, lines(hand))
+}
+
+/// Five cards and a bid, such as `32T3K 765`. Jokers show up as `*` once puz2 has swapped them
+/// in.
+fn hand(input: &str) -> PResult<'_, Hand> {
+    let cards = context(
+        "five cards",
+        take_while_m_n(5, 5, |card: char| "*23456789TJQKA".contains(card)),
+    );
+    let (input, (cards, bid)) = separated_pair(cards, space1, number)(input)?;
+
+    let hand: Vec<char> = cards.chars().collect();
+    Ok((
+        input,
+        Hand {
+            value: find_kind2(&hand),
+            hand,
+            bid,
+        },
+    ))
 }
 
 mod test {
-    use crate::day7::{
-        find_kind2, parse_cards, sort_and_score, FIVE_OF_A_KIND, FOUR_OF_A_KIND, FULL_HOUSE,
-        ONE_PAIR, THREE_OF_A_KIND, TWO_PAIR,
+    use crate::{
+        day7::{
+            find_kind2, parse_cards, sort_and_score, FIVE_OF_A_KIND, FOUR_OF_A_KIND, FULL_HOUSE,
+            ONE_PAIR, THREE_OF_A_KIND, TWO_PAIR,
+        },
+        runner::example,
     };
 
     #[test]
@@ -245,8 +302,8 @@ mod test {
     pub fn test1() {
         let test_data = test_data();
 
-        let cards = parse_cards(&test_data);
-        let answer = sort_and_score(cards);
+        let cards = parse_cards(&test_data).unwrap();
+        let answer = sort_and_score(cards).unwrap();
         assert_eq!(6440, answer);
     }
 
@@ -255,19 +312,14 @@ mod test {
         let mut test_data = test_data();
         // replace all the jacks with * so we can use the same code for puz1 and puz2
         test_data = test_data.replace('J', "*");
-        let cards = parse_cards(&test_data);
-        let answer = sort_and_score(cards);
+        let cards = parse_cards(&test_data).unwrap();
+        let answer = sort_and_score(cards).unwrap();
         println!("{answer}");
 
         assert_eq!(5905, answer);
     }
 
     fn test_data() -> String {
-        r"32T3K 765
-T55J5 684
-KK677 28
-KTJJT 220
-QQQJA 483"
-            .to_string()
+        example(7, "example")
     }
 }
@@ -1,19 +1,37 @@
-use std::{collections::HashMap, time::Instant, u128};
-
-use regex::Regex;
-
-use crate::common::file_io::read_file_to_string;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+};
+
+use nom::{bytes::complete::take_while1, error::context, sequence::separated_pair};
+
+use crate::{
+    common::{
+        answer::{Answer, Overflow, Solved, Unsolved},
+        file_io::read_file_to_string,
+        maths::checked_lcm,
+        parse::{blank_line, lines, node, parse_all, ParseError},
+        progress::{Progress, Stopped},
+    },
+    debug,
+    runner::Solution,
+};
 
 pub fn puz1() {
     let input = read_file_to_string("input/day8-input");
-    let (path, graph) = parse_input(&input);
-    let step_count = steps_from_to("AAA", "ZZZ", path, &graph);
-    println!("found ZZZ in {step_count} steps");
+    let (path, graph) =
+        parse_input(&input).unwrap_or_else(|e| panic!("failed to parse documents: {e}"));
+    match steps_from_to("AAA", "ZZZ", path, &graph, &mut Progress::new("day8 walk")) {
+        Ok(Some(step_count)) => println!("found ZZZ in {step_count} steps"),
+        Ok(None) => println!("there's no way from AAA to ZZZ"),
+        Err(stopped) => println!("walk {stopped}"),
+    }
 }
 
 pub fn puz2() {
     let input = read_file_to_string("input/day8-input");
-    let (path, graph) = parse_input(&input);
+    let (path, graph) =
+        parse_input(&input).unwrap_or_else(|e| panic!("failed to parse documents: {e}"));
 
     // find all start and end nodes
     let start_nodes: Vec<String> = graph
@@ -23,29 +41,85 @@ pub fn puz2() {
         .map(|s| String::from(s))
         .collect();
 
-    println!("{start_nodes:?}");
+    debug!("start nodes {start_nodes:?}");
 
     for start_node in start_nodes {
-        let count_for_node = steps_from_to(&start_node, "Z", path, &graph);
-
-        println!("Min route for {start_node} is {count_for_node}");
+        match steps_from_to(
+            &start_node,
+            "Z",
+            path,
+            &graph,
+            &mut Progress::new("day8 walk"),
+        ) {
+            Ok(Some(count_for_node)) => println!("Min route for {start_node} is {count_for_node}"),
+            Ok(None) => println!("{start_node} never reaches a Z node"),
+            Err(stopped) => println!("walk from {start_node} {stopped}"),
+        }
     }
 }
 
-const TEST_DATA_3: &str = r"LR
+/// The left/right instructions and the network they're followed through.
+pub struct Documents {
+    pub path: String,
+    pub graph: DesertMap,
+}
+
+pub fn parse(input: &str) -> Result<Documents, NetworkError> {
+    let (path, graph) = parse_input(input)?;
+    Ok(Documents {
+        path: String::from(path),
+        graph,
+    })
+}
+
+pub fn part1(documents: &Documents) -> Solved {
+    let mut progress = Progress::new("day8 walk");
+    let steps = steps_from_to(
+        "AAA",
+        "ZZZ",
+        &documents.path,
+        &documents.graph,
+        &mut progress,
+    )
+    .map_err(|stopped| Unsolved::NoAnswer(format!("walk {stopped}")))?
+    .ok_or_else(|| Unsolved::NoAnswer(String::from("there's no way from AAA to ZZZ")))?;
+    Ok(Answer::from(steps))
+}
+
+/// Every ghost loops back round to its Z node, so they all land on one together at the lowest
+/// common multiple of the steps each takes to get there the first time.
+pub fn part2(documents: &Documents) -> Solved {
+    let graph = &documents.graph;
+
+    let mut start_nodes: Vec<&String> = graph.map.keys().filter(|s| s.ends_with('A')).collect();
+    start_nodes.sort();
+    if start_nodes.is_empty() {
+        return Err(Unsolved::NoAnswer(String::from(
+            "no ghost starts on a node ending in A",
+        )));
+    }
 
-11A = (11B, XXX)
-11B = (XXX, 11Z)
-11Z = (11B, XXX)
-22A = (22B, XXX)
-22B = (22C, 22C)
-22C = (22Z, 22Z)
-22Z = (22B, 22B)
-XXX = (XXX, XXX)";
+    let mut progress = Progress::new("day8 ghost walks");
+    let mut total = 1;
+    for start_node in start_nodes {
+        let steps = steps_from_to(start_node, "Z", &documents.path, graph, &mut progress)
+            .map_err(|stopped| Unsolved::NoAnswer(format!("walk from {start_node} {stopped}")))?
+            .ok_or_else(|| {
+                Unsolved::NoAnswer(format!("the ghost at {start_node} never reaches a Z node"))
+            })?;
+        total = checked_lcm(total, steps).ok_or_else(Overflow::of::<u64>)?;
+    }
+    Ok(Answer::from(total))
+}
+
+pub fn solution() -> Solution {
+    Solution::new(8, parse, part1, part2)
+}
 
 pub fn brute() {
     let input = read_file_to_string("input/day8-input");
-    let (path, graph) = parse_input(&input);
+    let (path, graph) =
+        parse_input(&input).unwrap_or_else(|e| panic!("failed to parse documents: {e}"));
     let start_nodes: Vec<String> = graph
         .map
         .keys()
@@ -53,48 +127,115 @@ pub fn brute() {
         .map(|s| String::from(s))
         .collect();
 
-    let step_count = steps_from_to_para(start_nodes, "Z", path, &graph);
+    match steps_from_to_para(
+        start_nodes,
+        "Z",
+        path,
+        &graph,
+        &mut Progress::new("day8 ghost walk"),
+    ) {
+        Ok(step_count) => {
+            println!("Min route for all landing at the same time at Z is {step_count}")
+        }
+        Err(stopped) => println!("Ghost walk {stopped}"),
+    }
+}
+
+/// Why a network couldn't be read: a line that isn't a node, or nodes that don't join up.
+#[derive(Debug, PartialEq)]
+pub enum NetworkError {
+    Malformed(ParseError),
+    Duplicate(String),
+    Undefined(String),
+}
 
-    println!("Min route for all landing at the same time at Z is {step_count}")
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::Malformed(error) => write!(f, "not a network, {error}"),
+            NetworkError::Duplicate(node) => write!(f, "node {node} is defined more than once"),
+            NetworkError::Undefined(node) => write!(f, "node {node} is used but never defined"),
+        }
+    }
 }
 
-struct DesertMap {
+pub struct DesertMap {
     map: HashMap<String, (String, String)>,
 }
 
-fn parse_input(input: &str) -> (&str, DesertMap) {
-    // first line is steps
-    let input: Vec<&str> = input.split("\n").collect();
+impl DesertMap {
+    /// The left and right nodes reached from `node`.
+    pub fn next(&self, node: &str) -> Option<(&str, &str)> {
+        let (left, right) = self.map.get(node)?;
+        Some((left, right))
+    }
+
+    /// Where following `path` from `from`, repeating it as needed, ends up after `steps`
+    /// steps, or `None` if the walk reaches a node that isn't in the map. Each step is counted
+    /// on `progress`, which can stop the walk early.
+    pub fn walk<'a>(
+        &'a self,
+        from: &'a str,
+        path: &str,
+        steps: usize,
+        progress: &mut Progress,
+    ) -> Result<Option<&'a str>, Stopped> {
+        let mut node = from;
+        for direction in path.chars().cycle().take(steps) {
+            let Some((left, right)) = self.next(node) else {
+                return Ok(None);
+            };
+            node = if direction == 'L' { left } else { right };
+            progress.step()?;
+        }
+        Ok(Some(node))
+    }
+}
+
+fn parse_input(input: &str) -> Result<(&str, DesertMap), NetworkError> {
+    // first line is steps, then a blank line and the network one node to a line
+    let path = context(
+        "left and right steps",
+        take_while1(|c| c == 'L' || c == 'R'),
+    );
+    let (steps, nodes) = parse_all(input, separated_pair(path, blank_line, lines(node)))
+        .map_err(NetworkError::Malformed)?;
 
-    let steps = input[0].trim();
     let mut map = HashMap::new();
+    for (node, (left, right)) in &nodes {
+        match map.entry(String::from(*node)) {
+            Entry::Occupied(_) => return Err(NetworkError::Duplicate(String::from(*node))),
+            Entry::Vacant(slot) => {
+                slot.insert((String::from(*left), String::from(*right)));
+            }
+        }
+    }
 
-    // for the rest parse the map out
-    for line in &input[2..] {
-        let halfs: Vec<&str> = line.split("=").collect();
-
-        let node = halfs[0].trim();
-        let left_right = halfs[1].trim();
-        let left_right = left_right.replace("(", "");
-        let left_right = left_right.replace(")", "");
-        let left_right: Vec<&str> = left_right.split(",").map(|s| s.trim()).collect();
-        let left = left_right[0];
-        let right = left_right[1];
-
-        map.insert(
-            String::from(node),
-            (String::from(left), String::from(right)),
-        );
+    // every step has to land somewhere, so both sides of every node need defining
+    for (_, (left, right)) in &nodes {
+        for next in [left, right] {
+            if !map.contains_key(*next) {
+                return Err(NetworkError::Undefined(String::from(*next)));
+            }
+        }
     }
-    (steps, DesertMap { map: map })
+
+    Ok((steps, DesertMap { map }))
 }
 
-fn steps_from_to_para(from: Vec<String>, to: &str, path: &str, map: &DesertMap) -> u128 {
+/// Move every ghost at once until they're all on a node ending in `to`. Can take far too long
+/// on a real input, so it reports through `progress` and gives up when that says to.
+fn steps_from_to_para(
+    from: Vec<String>,
+    to: &str,
+    path: &str,
+    map: &DesertMap,
+    progress: &mut Progress,
+) -> Result<u128, Stopped> {
     let mut step_count: u128 = 0;
 
     let mut current_node_vec: Vec<&String> = from.iter().map(|f| f).collect();
-    println!("{current_node_vec:?}");
-    let now = Instant::now();
+    debug!("starting from {current_node_vec:?}");
 
     while !(current_node_vec.iter().all(|name| name.ends_with(to))) {
         for direction in path.chars() {
@@ -114,71 +255,81 @@ fn steps_from_to_para(from: Vec<String>, to: &str, path: &str, map: &DesertMap)
                 _ => panic!("step that wasn't L or R {direction}"),
             }
             step_count += 1;
-        }
-        if step_count % 100000 == 0 {
-            println!("{step_count}");
-            println!("{current_node_vec:?}");
-            let elapsed_time = now.elapsed();
-            println!(
-                "Running slow_function() has taken {} seconds.",
-                elapsed_time.as_secs()
-            );
+            progress.step()?;
         }
     }
-    step_count
+    Ok(step_count)
 }
 
-fn steps_from_to(from: &str, to: &str, path: &str, map: &DesertMap) -> u32 {
-    let mut step_count = 0;
-    let mut current_node = map.map.get(from).unwrap();
-    let mut current_node_name = from;
-
-    while current_node_name.ends_with(to) == false {
-        for direction in path.chars() {
-            match direction {
-                'L' => {
-                    current_node_name = &current_node.0;
-                    current_node = map.map.get(current_node_name).unwrap()
-                }
-                'R' => {
-                    current_node_name = &current_node.1;
-                    current_node = map.map.get(current_node_name).unwrap()
-                }
-                _ => panic!("step that wasn't L or R {direction}"),
-            }
-            step_count += 1;
+/// Steps from `from` to the first node ending in `to`, following `path` round and round, or
+/// `None` if `from` isn't in the map or the walk never gets there. Each step is counted on
+/// `progress`, which can stop the walk early.
+pub fn steps_from_to(
+    from: &str,
+    to: &str,
+    path: &str,
+    map: &DesertMap,
+    progress: &mut Progress,
+) -> Result<Option<u64>, Stopped> {
+    // once the walk has taken as many steps as there are node and path position pairs it has
+    // started going round in circles
+    let limit = map.map.len() as u64 * path.len() as u64;
+    let mut directions = path.chars().cycle();
 
-            // see if we're at the destination
-            if !current_node_name.ends_with(to) == false {
-                // reached destination
-                break;
-            }
+    let mut step_count = 0;
+    let Some((current_node, _)) = map.map.get_key_value(from) else {
+        return Ok(None);
+    };
+    let mut current_node_name = current_node.as_str();
+    while !current_node_name.ends_with(to) {
+        if step_count >= limit {
+            return Ok(None);
         }
+        let Some((left, right)) = map.next(current_node_name) else {
+            return Ok(None);
+        };
+        current_node_name = match directions.next() {
+            Some('L') => left,
+            Some(_) => right,
+            None => return Ok(None),
+        };
+        step_count += 1;
+        progress.step()?;
     }
-    step_count
+    Ok(Some(step_count))
 }
+
 mod test {
-    use crate::day8::{steps_from_to, steps_from_to_para, TEST_DATA_3};
+    use std::time::Duration;
+
+    use crate::{
+        common::progress::{Progress, Stopped},
+        day8::{steps_from_to, steps_from_to_para},
+        runner::example,
+    };
 
     use super::parse_input;
 
     #[test]
     pub fn test1() {
-        let (path, graph) = parse_input(TEST_DATA);
-        let step_count = steps_from_to("AAA", "ZZZ", path, &graph);
-        assert_eq!(step_count, 2);
+        let input = example(8, "example");
+        let (path, graph) = parse_input(&input).unwrap();
+        let step_count = steps_from_to("AAA", "ZZZ", path, &graph, &mut Progress::new("test"));
+        assert_eq!(step_count, Ok(Some(2)));
     }
 
     #[test]
     pub fn test2() {
-        let (path, graph) = parse_input(TEST_DATA_2);
-        let step_count = steps_from_to("AAA", "ZZZ", path, &graph);
-        assert_eq!(step_count, 6);
+        let input = example(8, "repeat");
+        let (path, graph) = parse_input(&input).unwrap();
+        let step_count = steps_from_to("AAA", "ZZZ", path, &graph, &mut Progress::new("test"));
+        assert_eq!(step_count, Ok(Some(6)));
     }
 
     #[test]
     pub fn test_brute() {
-        let (path, graph) = parse_input(TEST_DATA_3);
+        let input = example(8, "ghosts");
+        let (path, graph) = parse_input(&input).unwrap();
         let start_nodes: Vec<String> = graph
             .map
             .keys()
@@ -186,23 +337,40 @@ mod test {
             .map(|s| String::from(s))
             .collect();
 
-        let step_count = steps_from_to_para(start_nodes, "Z", path, &graph);
-        assert_eq!(step_count, 6);
+        let step_count =
+            steps_from_to_para(start_nodes, "Z", path, &graph, &mut Progress::new("test"));
+        assert_eq!(step_count, Ok(6));
     }
 
-    const TEST_DATA: &str = r"RL
+    #[test]
+    pub fn test_brute_times_out() {
+        let input = example(8, "ghosts");
+        let (path, graph) = parse_input(&input).unwrap();
 
-AAA = (BBB, CCC)
-BBB = (DDD, EEE)
-CCC = (ZZZ, GGG)
-DDD = (DDD, DDD)
-EEE = (EEE, EEE)
-GGG = (GGG, GGG)
-ZZZ = (ZZZ, ZZZ)";
+        // no node ends in Q so this would walk forever
+        let start_nodes = vec![String::from("11A"), String::from("22A")];
+        let mut progress = Progress::new("test").with_timeout(Duration::from_millis(10));
+        let stopped = steps_from_to_para(start_nodes, "Q", path, &graph, &mut progress);
 
-    const TEST_DATA_2: &str = r"LLR
+        assert!(matches!(stopped, Err(Stopped::TimedOut { .. })));
+    }
+
+    #[test]
+    pub fn test_walk() {
+        let input = example(8, "repeat");
+        let (path, graph) = parse_input(&input).unwrap();
 
-AAA = (BBB, BBB)
-BBB = (AAA, ZZZ)
-ZZZ = (ZZZ, ZZZ)";
+        assert_eq!(
+            Ok(Some("ZZZ")),
+            graph.walk("AAA", path, 6, &mut Progress::new("test"))
+        );
+        assert_eq!(
+            Ok(None),
+            graph.walk("QQQ", path, 1, &mut Progress::new("test"))
+        );
+
+        let mut progress = Progress::new("test").with_timeout(Duration::ZERO);
+        let stopped = graph.walk("AAA", path, usize::MAX, &mut progress);
+        assert!(matches!(stopped, Err(Stopped::TimedOut { .. })));
+    }
 }
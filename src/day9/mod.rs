@@ -1,99 +1,125 @@
-use crate::common::file_io::read_file_to_vec;
+use crate::{
+    common::{
+        answer::{checked_sum, Answer, Overflow, Solved},
+        file_io::read_file_to_string,
+        parse::{lines, numbers, parse_all, ParseError},
+    },
+    runner::Solution,
+    trace,
+};
 
 pub fn puz1() {
-    let lines = read_file_to_vec("input/day9-input");
-    let lines = read_data(lines);
+    let input = read_file_to_string("input/day9-input");
+    let lines = read_data(&input).unwrap_or_else(|e| panic!("failed to parse histories: {e}"));
 
     let mut answer = 0;
 
     for line in lines {
-        answer += predict_next_number(&line);
+        answer += predict_next_number(&line).unwrap_or_else(|e| panic!("puz1: {e}"));
     }
 
     println!("sum of all next numbers is {answer}");
 }
 
 pub fn puz2() {
-    let lines = read_file_to_vec("input/day9-input");
-    let mut lines = read_data(lines);
+    let input = read_file_to_string("input/day9-input");
+    let lines = read_data(&input).unwrap_or_else(|e| panic!("failed to parse histories: {e}"));
 
     let mut answer = 0;
 
     for mut line in lines {
         line.reverse();
-        answer += predict_next_number(&line);
+        answer += predict_next_number(&line).unwrap_or_else(|e| panic!("puz2: {e}"));
     }
 
     println!("sum of all first numbers is {answer}");
 }
 
-fn predict_next_number(input: &Vec<i32>) -> i32 {
-    println!("-----------");
-    println!("{input:?}");
-    let next_increment = predict_next_num_inner(input);
+pub fn parse(input: &str) -> Result<Vec<Vec<i32>>, ParseError> {
+    read_data(input)
+}
 
-    input[input.len() - 1] + next_increment
+pub fn part1(histories: &[Vec<i32>]) -> Solved {
+    let next = histories
+        .iter()
+        .map(|line| predict_next_number(line))
+        .collect::<Result<Vec<i32>, Overflow>>()?;
+    Ok(Answer::from(checked_sum(next)?))
 }
 
-fn predict_next_num_inner(input: &Vec<i32>) -> i32 {
-    if input.iter().all(|n| *n == 0) {
-        0
-    } else {
-        // construct the next line and call recusively
-        let mut next_line = Vec::new();
-        for (index, num) in input.iter().enumerate() {
-            if index < input.len() - 1 {
-                // combine with next index
-                next_line.push(input[index + 1] - num);
-            }
-        }
-        // println!("{next_line:?}");
-        println!("{next_line:?}");
-        let next_increment = predict_next_num_inner(&next_line);
-        let last_num = next_line[next_line.len() - 1];
-        next_line.push(last_num + next_increment);
-        next_line[next_line.len() - 1]
-    }
+pub fn part2(histories: &[Vec<i32>]) -> Solved {
+    let previous = histories
+        .iter()
+        .map(|line| {
+            let mut line = line.clone();
+            line.reverse();
+            predict_next_number(&line)
+        })
+        .collect::<Result<Vec<i32>, Overflow>>()?;
+    Ok(Answer::from(checked_sum(previous)?))
+}
+
+pub fn solution() -> Solution {
+    Solution::new(
+        9,
+        parse,
+        |histories| part1(histories),
+        |histories| part2(histories),
+    )
 }
 
-fn read_data(input: Vec<String>) -> Vec<Vec<i32>> {
-    let mut result: Vec<Vec<i32>> = Vec::new();
+pub fn predict_next_number(input: &[i32]) -> Result<i32, Overflow> {
+    trace!("predicting {input:?}");
+    let Some(last) = input.last() else {
+        return Ok(0);
+    };
+    let next_increment = predict_next_num_inner(input)?;
 
-    for line in input {
-        let nums: Vec<i32> = line
-            .split(" ")
-            .map(|l| l.trim())
-            .map(|num| num.parse::<i32>().unwrap())
-            .collect();
-        result.push(nums);
+    last.checked_add(next_increment)
+        .ok_or_else(Overflow::of::<i32>)
+}
+
+fn predict_next_num_inner(input: &[i32]) -> Result<i32, Overflow> {
+    // a single number has no differences to go on, so it's taken to stay the same
+    if input.len() < 2 || input.iter().all(|n| *n == 0) {
+        return Ok(0);
     }
 
-    result
+    // construct the next line and call recusively
+    let next_line = input
+        .windows(2)
+        .map(|pair| pair[1].checked_sub(pair[0]).ok_or_else(Overflow::of::<i32>))
+        .collect::<Result<Vec<i32>, Overflow>>()?;
+    trace!("differences {next_line:?}");
+    let next_increment = predict_next_num_inner(&next_line)?;
+    next_line[next_line.len() - 1]
+        .checked_add(next_increment)
+        .ok_or_else(Overflow::of::<i32>)
+}
+
+fn read_data(input: &str) -> Result<Vec<Vec<i32>>, ParseError> {
+    parse_all(input, lines(numbers))
 }
 
 mod test {
-    use crate::day9::{predict_next_number, read_data};
+    use crate::{
+        day9::{predict_next_number, read_data},
+        runner::example,
+    };
 
     #[test]
     pub fn test1() {
-        let lines = TEST_DATA.split("\n").map(|s| s.to_string()).collect();
-        let lines = read_data(lines);
+        let lines = read_data(&example(9, "example")).unwrap();
 
-        assert_eq!(18, predict_next_number(&lines[0]));
-        assert_eq!(28, predict_next_number(&lines[1]));
-        assert_eq!(68, predict_next_number(&lines[2]));
+        assert_eq!(Ok(18), predict_next_number(&lines[0]));
+        assert_eq!(Ok(28), predict_next_number(&lines[1]));
+        assert_eq!(Ok(68), predict_next_number(&lines[2]));
     }
 
     #[test]
     pub fn testNeg() {
-        let lines = vec![String::from(
-            "14 13 12 11 10 9 8 7 6 5 4 3 2 1 0 -1 -2 -3 -4 -5 -6",
-        )];
-        let lines = read_data(lines);
+        let lines = read_data("14 13 12 11 10 9 8 7 6 5 4 3 2 1 0 -1 -2 -3 -4 -5 -6").unwrap();
 
-        assert_eq!(-7, predict_next_number(&lines[0]));
+        assert_eq!(Ok(-7), predict_next_number(&lines[0]));
     }
-    const TEST_DATA: &str = r"0 3 6 9 12 15
-1 3 6 10 15 21
-10 13 16 21 30 45";
 }
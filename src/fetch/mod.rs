@@ -0,0 +1,255 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::info;
+
+/// When day 1 of 2023 unlocked, 2023-12-01 05:00 UTC. Each later day unlocks a day after the last.
+pub const FIRST_UNLOCK: u64 = 1701406800;
+
+pub const USER_AGENT: &str = concat!(
+    "aoc23-1/",
+    env!("CARGO_PKG_VERSION"),
+    " (github.com/TimJennings/aoc2023)"
+);
+
+/// Where to fetch inputs from and where to keep them.
+pub struct Fetcher {
+    pub base_url: String,
+    pub session: String,
+    // fetched inputs are kept here as dayN-input, which is where the days read them from
+    pub cache: PathBuf,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Fetched {
+    Downloaded(PathBuf),
+    Cached(PathBuf),
+}
+
+impl Fetcher {
+    /// Fetch from the puzzle site, or from `AOC_URL` if it's set, into the crate's `input/`.
+    /// The session token comes from `AOC_SESSION`, or failing that the first line of the file
+    /// named by `AOC_SESSION_FILE`, which defaults to `.aoc-session` in the crate root.
+    pub fn from_env(root: &Path) -> Result<Fetcher, String> {
+        let session = match env::var("AOC_SESSION") {
+            Ok(session) => session,
+            Err(_) => {
+                let file = env::var("AOC_SESSION_FILE")
+                    .map_or_else(|_| root.join(".aoc-session"), PathBuf::from);
+                let contents = fs::read_to_string(&file).map_err(|e| {
+                    format!(
+                        "no AOC_SESSION set and failed to read {}: {e}",
+                        file.display()
+                    )
+                })?;
+                String::from(contents.lines().next().unwrap_or("").trim())
+            }
+        };
+        if session.is_empty() {
+            return Err(String::from("the session token is empty"));
+        }
+
+        Ok(Fetcher {
+            base_url: env::var("AOC_URL")
+                .unwrap_or_else(|_| String::from("https://adventofcode.com")),
+            session,
+            cache: root.join("input"),
+        })
+    }
+
+    /// Day `day`'s input, downloaded unless an earlier fetch already stored it. Nothing is
+    /// requested before the day has unlocked at `now`.
+    pub fn fetch(&self, day: u32, now: SystemTime) -> Result<Fetched, String> {
+        if !(1..=25).contains(&day) {
+            return Err(format!("there is no day {day}, days run from 1 to 25"));
+        }
+
+        let path = self.cache.join(format!("day{day}-input"));
+        // an empty file is a placeholder left by new-day, not a fetched input
+        if fs::metadata(&path).is_ok_and(|metadata| metadata.len() > 0) {
+            return Ok(Fetched::Cached(path));
+        }
+
+        let wait = until_unlock(day, now);
+        if !wait.is_zero() {
+            let minutes = wait.as_secs().div_ceil(60);
+            return Err(format!(
+                "day {day} unlocks in {}h {}m",
+                minutes / 60,
+                minutes % 60
+            ));
+        }
+
+        let url = format!(
+            "{}/2023/day/{day}/input",
+            self.base_url.trim_end_matches('/')
+        );
+        info!("fetching {url}");
+        let input = ureq::get(&url)
+            .set("User-Agent", USER_AGENT)
+            .set("Cookie", &format!("session={}", self.session))
+            .call()
+            .map_err(|e| match e {
+                ureq::Error::Status(code, _) => format!("{url} answered {code}"),
+                e => format!("failed to fetch {url}: {e}"),
+            })?
+            .into_string()
+            .map_err(|e| format!("failed to read {url}: {e}"))?;
+
+        fs::create_dir_all(&self.cache)
+            .map_err(|e| format!("failed to create {}: {e}", self.cache.display()))?;
+        fs::write(&path, input).map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+        Ok(Fetched::Downloaded(path))
+    }
+}
+
+/// How long from `now` until day `day` unlocks, zero once it has.
+pub fn until_unlock(day: u32, now: SystemTime) -> Duration {
+    let unlock = UNIX_EPOCH + Duration::from_secs(FIRST_UNLOCK + u64::from(day - 1) * 86400);
+    unlock.duration_since(now).unwrap_or(Duration::ZERO)
+}
+
+/// `fetch <N>...`, download each day's input into `input/`.
+pub fn run(args: &[String]) {
+    if args.is_empty() {
+        eprintln!("usage: fetch <N>...");
+        process::exit(1);
+    }
+
+    let fetcher = match Fetcher::from_env(Path::new(env!("CARGO_MANIFEST_DIR"))) {
+        Ok(fetcher) => fetcher,
+        Err(e) => {
+            eprintln!("fetch: {e}");
+            process::exit(1);
+        }
+    };
+
+    let mut failed = false;
+    for day in args {
+        let fetched = day
+            .parse()
+            .map_err(|_| format!("{day:?} is not a day"))
+            .and_then(|day| fetcher.fetch(day, SystemTime::now()));
+        match fetched {
+            Ok(Fetched::Downloaded(path)) => println!("day {day}: fetched {}", path.display()),
+            Ok(Fetched::Cached(path)) => println!("day {day}: already have {}", path.display()),
+            Err(e) => {
+                eprintln!("fetch day {day}: {e}");
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        path::PathBuf,
+        sync::mpsc,
+        thread,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    use super::{until_unlock, Fetched, Fetcher, FIRST_UNLOCK, USER_AGENT};
+
+    /// Answer each request on a local port with `body`, sending the request's head back over
+    /// the channel.
+    fn mock_server(body: &'static str) -> (String, mpsc::Receiver<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let head: Vec<String> = BufReader::new(&stream)
+                    .lines()
+                    .map(Result::unwrap)
+                    .take_while(|line| !line.is_empty())
+                    .collect();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+                if sender.send(head).is_err() {
+                    return;
+                }
+            }
+        });
+
+        (url, receiver)
+    }
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc23-fetch-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_unlock_time() {
+        let unlock = UNIX_EPOCH + Duration::from_secs(FIRST_UNLOCK + 2 * 86400);
+
+        assert_eq!(
+            Duration::from_secs(60),
+            until_unlock(3, unlock - Duration::from_secs(60))
+        );
+        assert_eq!(Duration::ZERO, until_unlock(3, unlock));
+        assert_eq!(Duration::ZERO, until_unlock(1, unlock));
+    }
+
+    #[test]
+    fn test_fetch_once_then_cached() {
+        let (base_url, requests) = mock_server("0 3 6 9 12 15\n");
+        let fetcher = Fetcher {
+            base_url,
+            session: String::from("abc123"),
+            cache: cache_dir("once"),
+        };
+        let now = UNIX_EPOCH + Duration::from_secs(FIRST_UNLOCK + 9 * 86400);
+        let path = fetcher.cache.join("day9-input");
+
+        assert_eq!(Ok(Fetched::Downloaded(path.clone())), fetcher.fetch(9, now));
+        assert_eq!("0 3 6 9 12 15\n", fs::read_to_string(&path).unwrap());
+        let head = requests.recv().unwrap();
+        assert_eq!("GET /2023/day/9/input HTTP/1.1", head[0]);
+        let has = |header: &str| head.iter().any(|line| line.eq_ignore_ascii_case(header));
+        assert!(has("cookie: session=abc123"));
+        assert!(has(&format!("user-agent: {USER_AGENT}")));
+
+        assert_eq!(Ok(Fetched::Cached(path)), fetcher.fetch(9, now));
+        assert!(requests.try_recv().is_err());
+
+        fs::remove_dir_all(&fetcher.cache).unwrap();
+    }
+
+    #[test]
+    fn test_locked_day_is_not_requested() {
+        let (base_url, requests) = mock_server("");
+        let fetcher = Fetcher {
+            base_url,
+            session: String::from("abc123"),
+            cache: cache_dir("locked"),
+        };
+        let now = UNIX_EPOCH + Duration::from_secs(FIRST_UNLOCK + 9 * 86400 - 90 * 60);
+
+        assert_eq!(
+            Err(String::from("day 10 unlocks in 1h 30m")),
+            fetcher.fetch(10, now)
+        );
+        assert!(requests.try_recv().is_err());
+        assert!(!fetcher.cache.exists());
+    }
+}
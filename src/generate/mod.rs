@@ -0,0 +1,403 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::common::{maths::lcm, rng::Rng};
+
+/// Options for the `generate` subcommand, given as `--name value` pairs.
+struct Settings {
+    options: HashMap<String, String>,
+}
+
+impl Settings {
+    pub fn parse(args: &[String]) -> Result<Settings, String> {
+        let mut options = HashMap::new();
+        let mut args = args.iter();
+
+        while let Some(name) = args.next() {
+            let name = name
+                .strip_prefix("--")
+                .ok_or_else(|| format!("expected an option like --seed but found {name:?}"))?;
+            let value = args
+                .next()
+                .ok_or_else(|| format!("--{name} needs a value"))?;
+            options.insert(String::from(name), value.clone());
+        }
+
+        Ok(Settings { options })
+    }
+
+    pub fn number(&self, name: &str, default: u64) -> Result<u64, String> {
+        match self.options.get(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("--{name} expects a number but got {value:?}")),
+            None => Ok(default),
+        }
+    }
+
+    pub fn numbers(&self, name: &str, default: &[u64]) -> Result<Vec<u64>, String> {
+        match self.options.get(name) {
+            Some(value) => value
+                .split(',')
+                .map(|n| {
+                    n.trim().parse().map_err(|_| {
+                        format!("--{name} expects numbers like 3,5,7 but got {value:?}")
+                    })
+                })
+                .collect(),
+            None => Ok(default.to_vec()),
+        }
+    }
+}
+
+/// A generated puzzle input, plus the answer when the generator knows it by construction.
+pub(crate) struct Generated {
+    pub(crate) input: String,
+    pub(crate) note: Option<String>,
+}
+
+/// `generate <day> [--seed N] [--size N] [day options]`, writes the input to stdout and anything
+/// known about the answer to stderr.
+pub fn run(args: &[String]) {
+    let (day, options) = match args.split_first() {
+        Some((day, options)) => (day.as_str(), options),
+        None => {
+            eprintln!("usage: generate <5|7|8|10> [--seed N] [--size N] [options]");
+            return;
+        }
+    };
+
+    match generate(day, options) {
+        Ok(generated) => {
+            print!("{}", generated.input);
+            if let Some(note) = generated.note {
+                eprintln!("{note}");
+            }
+        }
+        Err(e) => eprintln!("generate day {day}: {e}"),
+    }
+}
+
+fn generate(day: &str, options: &[String]) -> Result<Generated, String> {
+    let settings = Settings::parse(options)?;
+    let mut rng = Rng::new(settings.number("seed", 1)?);
+
+    match day {
+        "5" => almanac(
+            &mut rng,
+            settings.number("layers", 7)? as usize,
+            settings.number("size", 30)? as usize,
+            settings.number("range", 4_000_000_000)?,
+        ),
+        "7" => Ok(camel_cards(
+            &mut rng,
+            settings.number("size", 1000)? as usize,
+            settings.number("jokers", 3)? as usize,
+        )),
+        "8" => desert_network(
+            &mut rng,
+            settings.number("size", 7)? as usize,
+            &settings.numbers("cycles", &[21, 35, 77, 91])?,
+        ),
+        "10" => Ok(pipe_maze(
+            &mut rng,
+            settings.number("size", 10)? as usize,
+            settings.number("fill", 50)?,
+        )),
+        _ => Err(String::from("no generator for this day, try 5, 7, 8 or 10")),
+    }
+}
+
+const ALMANAC_NAMES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+/// Day 5: an almanac with `layers` maps, each cutting `0..range` into `size` pieces and shuffling
+/// them, so every layer is a one to one mapping like the real input.
+fn almanac(rng: &mut Rng, layers: usize, size: usize, range: u64) -> Result<Generated, String> {
+    // the solver reads almanac numbers as i64
+    if range == 0 || range > i64::MAX as u64 {
+        return Err(format!("range needs to be between 1 and {}", i64::MAX));
+    }
+    let size = size.clamp(1, range.min(usize::MAX as u64) as usize);
+
+    let mut input = String::from("seeds:");
+    for _ in 0..10 {
+        let start = rng.below(range);
+        let length = rng.between(1, (range - start).min(range / 10).max(1));
+        input += &format!(" {start} {length}");
+    }
+
+    for layer in 0..layers {
+        let name = |n: usize| match layers {
+            7 => String::from(ALMANAC_NAMES[n]),
+            _ => format!("layer{n}"),
+        };
+        input += &format!("\n\n{}-to-{} map:", name(layer), name(layer + 1));
+
+        // cut points split 0..range into `size` pieces
+        let mut cuts: Vec<u64> = (1..size).map(|_| rng.between(1, range - 1)).collect();
+        cuts.extend([0, range]);
+        cuts.sort();
+        cuts.dedup();
+        let pieces: Vec<(u64, u64)> = cuts.windows(2).map(|w| (w[0], w[1] - w[0])).collect();
+
+        // lay the pieces out again in a random order to find where each one lands
+        let mut order: Vec<usize> = (0..pieces.len()).collect();
+        rng.shuffle(&mut order);
+        let mut destination = 0;
+        let mut maps = Vec::new();
+        for index in order {
+            let (source, length) = pieces[index];
+            maps.push((destination, source, length));
+            destination += length;
+        }
+        rng.shuffle(&mut maps);
+
+        for (destination, source, length) in maps {
+            input += &format!("\n{destination} {source} {length}");
+        }
+    }
+
+    Ok(Generated { input, note: None })
+}
+
+/// Day 7: `size` hands with up to `jokers` jacks in each and bids up to 1000.
+fn camel_cards(rng: &mut Rng, size: usize, jokers: usize) -> Generated {
+    const CARDS: [char; 12] = ['2', '3', '4', '5', '6', '7', '8', '9', 'T', 'Q', 'K', 'A'];
+
+    let mut hands = Vec::new();
+    for _ in 0..size {
+        let joker_count = rng.between(0, jokers.min(5) as u64) as usize;
+        let mut hand: Vec<char> = (0..5).map(|_| *rng.pick(&CARDS)).collect();
+        hand[..joker_count].fill('J');
+        rng.shuffle(&mut hand);
+
+        let hand: String = hand.into_iter().collect();
+        hands.push(format!("{hand} {}", rng.between(1, 1000)));
+    }
+
+    Generated {
+        input: hands.join("\n"),
+        note: None,
+    }
+}
+
+/// Day 8: one ghost per entry in `cycles`, each reaching its `Z` node for the first time after
+/// that many steps and every that many steps after. `size` is the length of the L/R path. The
+/// parallel solver only checks for a finish at the end of the path, like the real input the
+/// cycle lengths need to be multiples of `size` for it to find one.
+fn desert_network(rng: &mut Rng, size: usize, cycles: &[u64]) -> Result<Generated, String> {
+    if size == 0 || cycles.iter().any(|c| *c < 2) {
+        return Err(String::from(
+            "path needs a step and cycles need at least two steps",
+        ));
+    }
+
+    let path: String = (0..size)
+        .map(|_| if rng.chance(50) { 'L' } else { 'R' })
+        .collect();
+
+    let mut nodes = Vec::new();
+    for (ghost, cycle) in cycles.iter().enumerate() {
+        // every step along the cycle has a node reached by going left and one by going right,
+        // the last step lands on the Z node either way
+        let step = |n: u64, side: char| match n {
+            0 => format!("G{ghost}A"),
+            n if n == *cycle => format!("G{ghost}Z"),
+            n => format!("G{ghost}N{n}{side}"),
+        };
+        let next = |n: u64| {
+            let n = if n == *cycle { 1 } else { n + 1 };
+            (step(n, 'L'), step(n, 'R'))
+        };
+
+        let (left, right) = next(0);
+        nodes.push(format!("{} = ({left}, {right})", step(0, 'L')));
+        for n in 1..*cycle {
+            let (left, right) = next(n);
+            for side in ['L', 'R'] {
+                nodes.push(format!("{} = ({left}, {right})", step(n, side)));
+            }
+        }
+        let (left, right) = next(*cycle);
+        nodes.push(format!("{} = ({left}, {right})", step(*cycle, 'L')));
+    }
+
+    // keep the classic AAA to ZZZ route working for part 1
+    nodes.push(String::from("AAA = (ZZZ, ZZZ)"));
+    nodes.push(String::from("ZZZ = (ZZZ, ZZZ)"));
+    rng.shuffle(&mut nodes);
+
+    let all_together = cycles.iter().fold(1, |acc, c| lcm(acc, *c));
+    Ok(Generated {
+        input: format!("{path}\n\n{}", nodes.join("\n")),
+        note: Some(format!(
+            "cycles {cycles:?}, all ghosts on Z after {all_together} steps"
+        )),
+    })
+}
+
+/// Day 10: a loop of pipe around a random tree of corridors. The tree is grown on a `size` by
+/// `size` grid of cells spaced four tiles apart, so the loop hugging it never touches itself and
+/// the enclosed area is exactly the tiles of the tree. `fill` is the percentage of cells the tree
+/// covers. Everything off the loop is filled with junk pipe.
+pub(crate) fn pipe_maze(rng: &mut Rng, size: usize, fill: u64) -> Generated {
+    const SCALE: usize = 4;
+    const JUNK: [char; 7] = ['.', '|', '-', 'L', 'J', '7', 'F'];
+
+    let size = size.max(1);
+    let tiles = size * SCALE + 1;
+    let centre = |cell: usize| cell * SCALE + 2;
+
+    // grow a random tree over the cells
+    let target = ((size * size) as u64 * fill / 100).max(1) as usize;
+    let start = (
+        rng.below(size as u64) as usize,
+        rng.below(size as u64) as usize,
+    );
+    let mut in_tree: HashSet<(usize, usize)> = HashSet::from([start]);
+    let mut region: HashSet<(usize, usize)> = HashSet::from([(centre(start.0), centre(start.1))]);
+    let mut frontier: Vec<((usize, usize), (usize, usize))> = Vec::new();
+    let neighbours = |(x, y): (usize, usize)| {
+        let mut cells = Vec::new();
+        if x > 0 {
+            cells.push((x - 1, y));
+        }
+        if y > 0 {
+            cells.push((x, y - 1));
+        }
+        if x + 1 < size {
+            cells.push((x + 1, y));
+        }
+        if y + 1 < size {
+            cells.push((x, y + 1));
+        }
+        cells
+    };
+    frontier.extend(neighbours(start).into_iter().map(|n| (start, n)));
+
+    while in_tree.len() < target && !frontier.is_empty() {
+        let (from, to) = frontier.swap_remove(rng.below(frontier.len() as u64) as usize);
+        if in_tree.contains(&to) {
+            continue;
+        }
+        in_tree.insert(to);
+
+        // corridor from the centre of one cell to the next
+        let (fx, fy) = (centre(from.0), centre(from.1));
+        let (tx, ty) = (centre(to.0), centre(to.1));
+        for x in fx.min(tx)..=fx.max(tx) {
+            for y in fy.min(ty)..=fy.max(ty) {
+                region.insert((x, y));
+            }
+        }
+        frontier.extend(neighbours(to).into_iter().map(|n| (to, n)));
+    }
+
+    // the loop is every tile touching the tree, diagonals included, that isn't in it
+    let mut on_loop: HashSet<(usize, usize)> = HashSet::new();
+    for (x, y) in &region {
+        for nx in x - 1..=x + 1 {
+            for ny in y - 1..=y + 1 {
+                if !region.contains(&(nx, ny)) {
+                    on_loop.insert((nx, ny));
+                }
+            }
+        }
+    }
+
+    let mut grid: Vec<Vec<char>> = (0..tiles)
+        .map(|_| (0..tiles).map(|_| *rng.pick(&JUNK)).collect())
+        .collect();
+
+    for (x, y) in &on_loop {
+        let north = on_loop.contains(&(x - 1, *y));
+        let south = on_loop.contains(&(x + 1, *y));
+        let west = on_loop.contains(&(*x, y - 1));
+        let east = on_loop.contains(&(*x, y + 1));
+        grid[*x][*y] = match (north, south, east, west) {
+            (true, true, false, false) => '|',
+            (false, false, true, true) => '-',
+            (true, false, true, false) => 'L',
+            (true, false, false, true) => 'J',
+            (false, true, false, true) => '7',
+            (false, true, true, false) => 'F',
+            _ => unreachable!("loop tile at {x},{y} doesn't have exactly two neighbours"),
+        };
+    }
+
+    // start on a random loop tile, junk next to it mustn't look connected
+    let mut loop_tiles: Vec<&(usize, usize)> = on_loop.iter().collect();
+    loop_tiles.sort();
+    let (sx, sy) = **rng.pick(&loop_tiles);
+    grid[sx][sy] = 'S';
+    for (nx, ny) in [(sx - 1, sy), (sx + 1, sy), (sx, sy - 1), (sx, sy + 1)] {
+        if !on_loop.contains(&(nx, ny)) {
+            grid[nx][ny] = '.';
+        }
+    }
+
+    let input: Vec<String> = grid
+        .into_iter()
+        .map(|row| row.into_iter().collect())
+        .collect();
+    Generated {
+        input: input.join("\n"),
+        note: Some(format!(
+            "loop length {}, enclosed area {}",
+            on_loop.len(),
+            region.len()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, Settings};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| String::from(*s)).collect()
+    }
+
+    #[test]
+    fn test_settings() {
+        let settings = Settings::parse(&args(&["--seed", "9", "--cycles", "2,4"])).unwrap();
+
+        assert_eq!(9, settings.number("seed", 1).unwrap());
+        assert_eq!(30, settings.number("size", 30).unwrap());
+        assert_eq!(vec![2, 4], settings.numbers("cycles", &[]).unwrap());
+        assert!(Settings::parse(&args(&["--seed"])).is_err());
+        assert!(Settings::parse(&args(&["seed", "1"])).is_err());
+    }
+
+    #[test]
+    fn test_same_seed_same_input() {
+        for day in ["5", "7", "8", "10"] {
+            let first = generate(day, &args(&["--seed", "3"])).unwrap().input;
+            let second = generate(day, &args(&["--seed", "3"])).unwrap().input;
+            let other = generate(day, &args(&["--seed", "4"])).unwrap().input;
+
+            assert_eq!(first, second);
+            assert_ne!(first, other);
+        }
+    }
+
+    #[test]
+    fn test_bad_almanac_range() {
+        assert!(generate("5", &args(&["--range", "0"])).is_err());
+        assert!(generate("5", &args(&["--range", "18446744073709551615"])).is_err());
+        assert!(generate("5", &args(&["--range", "1", "--size", "5"])).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_day() {
+        assert!(generate("1", &[]).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+//! Advent of Code 2023 solutions. Each `dayN` module parses its puzzle input into a model and
+//! solves both parts from it, and `runner` collects every day behind one interface.
+
+pub mod common;
+pub mod day1;
+pub mod day10;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod fetch;
+pub mod generate;
+pub mod repl;
+pub mod runner;
+pub mod scaffold;
+pub mod submit;
@@ -1,34 +1,127 @@
-mod common;
-mod day1;
-mod day10;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-use std::{env, time::Instant};
+use std::{
+    env, process,
+    time::{Duration, Instant},
+};
+
+use aoc23_1::{
+    common::{
+        log::{self, Filter},
+        progress,
+    },
+    day1, day10, day2, day3, day4, day5, day6, day7, day8, day9, fetch, generate, repl, runner,
+    scaffold, submit,
+};
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let format = take_option(&mut args, "--format");
+
+    // give up on long searches after this many seconds
+    if let Some(timeout) = take_option(&mut args, "--timeout") {
+        let duration = timeout
+            .parse::<f64>()
+            .map_err(|_| format!("--timeout takes a number of seconds, not {timeout:?}"))
+            .and_then(|seconds| {
+                Duration::try_from_secs_f64(seconds)
+                    .map_err(|e| format!("--timeout {timeout}: {e}"))
+            });
+        match duration {
+            Ok(duration) => progress::set_default_timeout(duration),
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+        }
+    }
+
+    // e.g. -v debug or -v day9=trace, debug output is off without it
+    if let Some(spec) = take_option(&mut args, "-v") {
+        match Filter::parse(&spec) {
+            Ok(filter) => log::init(filter),
+            Err(e) => {
+                eprintln!("bad -v filter: {e}");
+                process::exit(1);
+            }
+        }
+    }
+
+    if args.get(1).is_some_and(|s| s == "generate") {
+        generate::run(&args[2..]);
+        return;
+    }
+
+    if args.get(1).is_some_and(|s| s == "fetch") {
+        fetch::run(&args[2..]);
+        return;
+    }
+
+    if args.get(1).is_some_and(|s| s == "submit") {
+        submit::run(&args[2..]);
+        return;
+    }
+
+    if args.get(1).is_some_and(|s| s == "new-day") {
+        scaffold::run(&args[2..]);
+        return;
+    }
+
+    if args.get(1).is_some_and(|s| s == "repl") {
+        repl::run(&args[2..]);
+        return;
+    }
+
+    if args.get(1).is_some_and(|s| s == "compare") {
+        runner::compare::run(&args[2..]);
+        return;
+    }
+
+    if args.get(1).is_some_and(|s| s == "watch") {
+        runner::watch::run(&args[2..]);
+        return;
+    }
+
+    if args.get(1).is_some_and(|s| s == "examples") {
+        runner::run_examples(args.get(2).map(|s| s.as_str()));
+        return;
+    }
+
     let day = if args.len() >= 2 { &args[1] } else { "10" };
 
+    match format.as_deref() {
+        None | Some("text") => {}
+        Some("json") => {
+            runner::print_json(day);
+            return;
+        }
+        Some(format) => {
+            eprintln!("unknown format {format:?}, expected text or json");
+            process::exit(1);
+        }
+    }
+
     println!("Running day {day}");
 
     match day {
         "1" => {
             day1::puz1();
             day1::puz2();
+            // optional vocabulary file of "<word> <digit>" lines
+            if let Some(vocabulary) = args.get(2) {
+                day1::puz_with_vocabulary(vocabulary);
+            }
         }
         "2" => {
             day2::puz1();
             day2::puz2();
         }
-        "3" => {
-            day3::puz1();
-            day3::puz2();
-        }
+        "3" => match args.get(2).map(|s| s.as_str()) {
+            Some("render") => day3::render(args.get(3).is_some_and(|s| s == "plain")),
+            Some("stream") => day3::stream(args.get(3).map_or("input/day3-input", |s| s.as_str())),
+            _ => {
+                day3::puz1();
+                day3::puz2();
+            }
+        },
         "4" => {
             //day4::puz1();
             let now = Instant::now();
@@ -73,5 +166,49 @@ fn main() {
     }
 }
 
+/// Remove `name <value>` from the arguments wherever it appears and return the value, exiting
+/// if `name` is the last argument and so has no value.
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    split_option(args, name).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    })
+}
+
+fn split_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    let Some(index) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+    args.remove(index);
+    if index < args.len() {
+        Ok(Some(args.remove(index)))
+    } else {
+        Err(format!("{name} is missing its value"))
+    }
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::split_option;
+
+    #[test]
+    fn test_split_option() {
+        let mut args: Vec<String> = ["aoc", "--format", "json", "7"]
+            .iter()
+            .map(|arg| String::from(*arg))
+            .collect();
+
+        assert_eq!(
+            Ok(Some(String::from("json"))),
+            split_option(&mut args, "--format")
+        );
+        assert_eq!(vec!["aoc", "7"], args);
+        assert_eq!(Ok(None), split_option(&mut args, "--timeout"));
+
+        args.push(String::from("--timeout"));
+        assert_eq!(
+            Err(String::from("--timeout is missing its value")),
+            split_option(&mut args, "--timeout")
+        );
+    }
+}
@@ -0,0 +1,463 @@
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    process,
+    time::Duration,
+};
+
+use crate::{
+    common::progress::Progress,
+    day3::{self, Grid},
+    day5::Almanac,
+    day8::{self, Documents},
+};
+
+// a walk can be asked for any number of steps, so give up on one that runs this long
+const WALK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One query a day's model answers, as listed by `help`.
+pub struct Command {
+    pub name: &'static str,
+    pub args: &'static str,
+    pub help: &'static str,
+}
+
+/// A parsed model that can be queried from the REPL.
+pub trait Queries {
+    fn commands(&self) -> &'static [Command];
+
+    /// Answer `name` with `args`, or say what was wrong with them.
+    fn query(&self, name: &str, args: &[&str]) -> Result<String, String>;
+}
+
+fn arg<T: std::str::FromStr>(args: &[&str], index: usize, what: &str) -> Result<T, String> {
+    let text = args.get(index).ok_or_else(|| format!("missing {what}"))?;
+    text.parse().map_err(|_| format!("{text:?} isn't a {what}"))
+}
+
+impl Queries for Almanac {
+    fn commands(&self) -> &'static [Command] {
+        &[
+            Command {
+                name: "seeds",
+                args: "",
+                help: "list the seeds",
+            },
+            Command {
+                name: "map",
+                args: "<seed>",
+                help: "follow a seed through every layer to its location",
+            },
+            Command {
+                name: "layer",
+                args: "<n>",
+                help: "list layer n's maps as destination source length, from 1",
+            },
+        ]
+    }
+
+    fn query(&self, name: &str, args: &[&str]) -> Result<String, String> {
+        match name {
+            "seeds" => Ok(self
+                .seeds
+                .iter()
+                .map(i64::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")),
+            "map" => {
+                let mut value: i64 = arg(args, 0, "seed")?;
+                let mut steps = vec![value.to_string()];
+                for layer in &self.layers {
+                    value = layer
+                        .iter()
+                        .find_map(|map| map.convert(value))
+                        .unwrap_or(value);
+                    steps.push(value.to_string());
+                }
+                Ok(steps.join(" -> "))
+            }
+            "layer" => {
+                let n: usize = arg(args, 0, "layer number")?;
+                let layer = n
+                    .checked_sub(1)
+                    .and_then(|index| self.layers.get(index))
+                    .ok_or_else(|| format!("there are {} layers", self.layers.len()))?;
+                Ok(layer
+                    .iter()
+                    .map(|map| {
+                        format!(
+                            "{} {} {}",
+                            map.destination_range_start, map.source_range_start, map.range_length
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+            _ => Err(format!("no command {name:?}")),
+        }
+    }
+}
+
+impl Queries for Documents {
+    fn commands(&self) -> &'static [Command] {
+        &[
+            Command {
+                name: "path",
+                args: "",
+                help: "show the left and right steps",
+            },
+            Command {
+                name: "node",
+                args: "<name>",
+                help: "show where a node leads left and right",
+            },
+            Command {
+                name: "walk",
+                args: "<from> <steps>",
+                help: "follow the path from a node and show where it ends up",
+            },
+        ]
+    }
+
+    fn query(&self, name: &str, args: &[&str]) -> Result<String, String> {
+        match name {
+            "path" => Ok(self.path.clone()),
+            "node" => {
+                let node: String = arg(args, 0, "node")?;
+                let (left, right) = self
+                    .graph
+                    .next(&node)
+                    .ok_or_else(|| format!("no node {node}"))?;
+                Ok(format!("{node} = ({left}, {right})"))
+            }
+            "walk" => {
+                let from: String = arg(args, 0, "node")?;
+                let steps: usize = arg(args, 1, "number of steps")?;
+                let mut progress = Progress::new("repl walk").with_timeout(WALK_TIMEOUT);
+                let to = self
+                    .graph
+                    .walk(&from, &self.path, steps, &mut progress)
+                    .map_err(|stopped| format!("walk from {from} {stopped}"))?
+                    .ok_or_else(|| format!("walked off the map from {from}"))?;
+                Ok(format!("{from} -> {to} after {steps} steps"))
+            }
+            _ => Err(format!("no command {name:?}")),
+        }
+    }
+}
+
+impl Queries for Grid {
+    fn commands(&self) -> &'static [Command] {
+        &[
+            Command {
+                name: "near",
+                args: "<row> <column>",
+                help: "list the numbers touching a cell, counting from 0",
+            },
+            Command {
+                name: "parts",
+                args: "",
+                help: "count and total the part numbers",
+            },
+        ]
+    }
+
+    fn query(&self, name: &str, args: &[&str]) -> Result<String, String> {
+        match name {
+            "near" => {
+                let row = arg(args, 0, "row")?;
+                let column = arg(args, 1, "column")?;
+                let numbers: Vec<String> = self
+                    .numbers_adjacent_to(row, column)
+                    .iter()
+                    .map(|number| {
+                        let (row, start, end) = number.position();
+                        format!("{} at row {row}, columns {start}-{end}", number.value())
+                    })
+                    .collect();
+                if numbers.is_empty() {
+                    Ok(format!("nothing touches ({row}, {column})"))
+                } else {
+                    Ok(numbers.join("\n"))
+                }
+            }
+            "parts" => {
+                let parts = self.part_numbers();
+                Ok(format!(
+                    "{} part numbers totalling {}",
+                    parts.len(),
+                    self.total_parts()
+                        .map_err(|overflow| overflow.to_string())?
+                ))
+            }
+            _ => Err(format!("no command {name:?}")),
+        }
+    }
+}
+
+/// Parse `input` into the model for `day`, for the days that have queries.
+pub fn model(day: u32, input: &str) -> Result<Box<dyn Queries>, String> {
+    match day {
+        3 => Ok(Box::new(day3::parse(input).map_err(|e| e.to_string())?)),
+        5 => Ok(Box::new(Almanac::parse(input).map_err(|e| e.to_string())?)),
+        8 => Ok(Box::new(day8::parse(input).map_err(|e| e.to_string())?)),
+        _ => Err(String::from("no queries for this day, try 3, 5 or 8")),
+    }
+}
+
+/// What to do after a line has been read.
+#[derive(Debug, PartialEq)]
+pub enum Reply {
+    Show(String),
+    Quit,
+}
+
+/// The commands every day has.
+const BUILT_IN: [Command; 3] = [
+    Command {
+        name: "help",
+        args: "",
+        help: "list the commands",
+    },
+    Command {
+        name: "history",
+        args: "",
+        help: "list earlier commands, rerun one with !<n> or the last with !!",
+    },
+    Command {
+        name: "quit",
+        args: "",
+        help: "leave",
+    },
+];
+
+fn help(queries: &dyn Queries) -> String {
+    let commands: Vec<&Command> = queries.commands().iter().chain(&BUILT_IN).collect();
+    let usages: Vec<String> = commands
+        .iter()
+        .map(|command| {
+            format!("{} {}", command.name, command.args)
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+    let width = usages.iter().map(String::len).max().unwrap_or(0);
+
+    commands
+        .iter()
+        .zip(&usages)
+        .map(|(command, usage)| format!("{usage:<width$}  {}", command.help))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Answer one line typed at the prompt. Lines that ran a command are added to `history`, with
+/// `!!` and `!<n>` replaced by the command they recall.
+pub fn evaluate(queries: &dyn Queries, history: &mut Vec<String>, line: &str) -> Reply {
+    let line = line.trim();
+    let line = if line == "!!" {
+        match history.last() {
+            Some(last) => last.clone(),
+            None => return Reply::Show(String::from("no history yet")),
+        }
+    } else if let Some(n) = line.strip_prefix('!') {
+        match n
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| history.get(n.checked_sub(1)?))
+        {
+            Some(recalled) => recalled.clone(),
+            None => return Reply::Show(format!("no command {n} in the history")),
+        }
+    } else {
+        String::from(line)
+    };
+
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some((&name, args)) = words.split_first() else {
+        return Reply::Show(String::new());
+    };
+
+    let reply = match name {
+        "quit" | "exit" => return Reply::Quit,
+        "help" => help(queries),
+        "history" => history
+            .iter()
+            .enumerate()
+            .map(|(index, command)| format!("{:>4}  {command}", index + 1))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => queries
+            .query(name, args)
+            .unwrap_or_else(|e| format!("error: {e}")),
+    };
+    if name != "history" {
+        history.push(line.clone());
+    }
+    Reply::Show(reply)
+}
+
+/// `repl <day> [input file]`, parse the day's input once and answer queries about it. History
+/// is kept between sessions in `.repl-history`.
+pub fn run(args: &[String]) {
+    let Some(day) = args.first().and_then(|day| day.parse::<u32>().ok()) else {
+        eprintln!("usage: repl <day> [input file]");
+        process::exit(1);
+    };
+
+    let file_path = args
+        .get(1)
+        .cloned()
+        .unwrap_or_else(|| format!("input/day{day}-input"));
+    let input = fs::read_to_string(&file_path).unwrap_or_else(|e| {
+        eprintln!("failed to read {file_path}: {e}");
+        process::exit(1);
+    });
+    let queries = model(day, &input).unwrap_or_else(|e| {
+        eprintln!("repl day {day}: {e}");
+        process::exit(1);
+    });
+
+    let history_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(".repl-history");
+    let mut history: Vec<String> = fs::read_to_string(&history_path)
+        .map(|contents| contents.lines().map(String::from).collect())
+        .unwrap_or_default();
+    let saved = history.len();
+
+    println!("day {day} parsed from {file_path}, type help for commands");
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("day{day}> ");
+        let _ = io::stdout().flush();
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        match evaluate(&*queries, &mut history, &line) {
+            Reply::Show(text) if text.is_empty() => {}
+            Reply::Show(text) => println!("{text}"),
+            Reply::Quit => break,
+        }
+    }
+
+    let mut new: String = history[saved..]
+        .iter()
+        .map(|command| format!("{command}\n"))
+        .collect();
+    if !new.is_empty() {
+        if let Ok(contents) = fs::read_to_string(&history_path) {
+            new.insert_str(0, &contents);
+        }
+        if let Err(e) = fs::write(&history_path, new) {
+            eprintln!("failed to save history to {}: {e}", history_path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate, model, Reply};
+    use crate::{common::progress::Canceller, runner::example};
+
+    fn show(text: &str) -> Reply {
+        Reply::Show(String::from(text))
+    }
+
+    #[test]
+    fn test_almanac_queries() {
+        let almanac = model(5, &example(5, "example")).unwrap();
+        let mut history = Vec::new();
+
+        assert_eq!(
+            show("79 14 55 13"),
+            evaluate(&*almanac, &mut history, "seeds")
+        );
+        assert_eq!(
+            show("79 -> 81 -> 81 -> 81 -> 74 -> 78 -> 78 -> 82"),
+            evaluate(&*almanac, &mut history, "map 79")
+        );
+        assert_eq!(
+            show("50 98 2\n52 50 48"),
+            evaluate(&*almanac, &mut history, "layer 1")
+        );
+        assert_eq!(
+            show("error: \"x\" isn't a seed"),
+            evaluate(&*almanac, &mut history, "map x")
+        );
+    }
+
+    #[test]
+    fn test_network_and_grid_queries() {
+        let documents = model(8, &example(8, "ghosts")).unwrap();
+        let mut history = Vec::new();
+        assert_eq!(
+            show("11A = (11B, XXX)"),
+            evaluate(&*documents, &mut history, "node 11A")
+        );
+        assert_eq!(
+            show("11A -> 11Z after 100 steps"),
+            evaluate(&*documents, &mut history, "walk 11A 100")
+        );
+
+        let grid = model(3, &example(3, "example")).unwrap();
+        assert_eq!(
+            show("467 at row 0, columns 0-2\n35 at row 2, columns 2-3"),
+            evaluate(&*grid, &mut history, "near 1 3")
+        );
+    }
+
+    #[test]
+    fn test_long_walks_stop() {
+        let documents = model(8, &example(8, "ghosts")).unwrap();
+        let canceller = Canceller::new();
+        canceller.install();
+        canceller.cancel();
+
+        assert_eq!(
+            show("error: walk from 11A cancelled after 4096 steps"),
+            evaluate(
+                &*documents,
+                &mut Vec::new(),
+                "walk 11A 18446744073709551615"
+            )
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let almanac = model(5, &example(5, "example")).unwrap();
+        let mut history = Vec::new();
+
+        evaluate(&*almanac, &mut history, "map 79");
+        evaluate(&*almanac, &mut history, "seeds");
+        assert_eq!(
+            show("79 -> 81 -> 81 -> 81 -> 74 -> 78 -> 78 -> 82"),
+            evaluate(&*almanac, &mut history, "!1")
+        );
+        assert_eq!(
+            show("   1  map 79\n   2  seeds\n   3  map 79"),
+            evaluate(&*almanac, &mut history, "history")
+        );
+        assert_eq!(
+            show("no command 9 in the history"),
+            evaluate(&*almanac, &mut history, "!9")
+        );
+        assert_eq!(Reply::Quit, evaluate(&*almanac, &mut history, "quit"));
+    }
+
+    #[test]
+    fn test_help_lists_day_commands() {
+        let Reply::Show(help) = evaluate(
+            &*model(8, "LR\n\nAAA = (AAA, AAA)").unwrap(),
+            &mut Vec::new(),
+            "help",
+        ) else {
+            panic!("help should show something");
+        };
+
+        assert!(help.contains("walk <from> <steps>  follow the path"));
+        assert!(help.contains("history"));
+        assert!(model(9, "").is_err());
+    }
+}
@@ -0,0 +1,276 @@
+use std::{fs, path::Path, process, time::Duration};
+
+use crate::{
+    common::progress,
+    runner::{
+        record::{measure_within, Record, Status},
+        solution,
+    },
+};
+
+// how long each input gets when --timeout isn't given
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Run both parts of `day` over every file in `dir`, in name order, giving each input at most
+/// `timeout`.
+pub fn compare(
+    day: u32,
+    dir: &Path,
+    timeout: Duration,
+) -> Result<Vec<(String, Vec<Record>)>, String> {
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| format!("failed to read {}: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+
+    files
+        .into_iter()
+        .map(|path| {
+            let name = path
+                .file_name()
+                .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+            let input = fs::read_to_string(&path)
+                .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+            Ok((name, measure_within(day, &input, timeout)))
+        })
+        .collect()
+}
+
+fn cell(record: &Record) -> String {
+    match &record.answer {
+        Some(answer) => answer.to_string(),
+        None => format!("[{}]", record.status.name()),
+    }
+}
+
+/// A table of each file's answers and timings, followed by a line for every part that didn't
+/// come back with an answer, the full text of any error too long for that line, and a warning
+/// if any of them timed out.
+pub fn table(results: &[(String, Vec<Record>)]) -> Vec<String> {
+    let mut rows = vec![[
+        String::from("file"),
+        String::from("parse"),
+        String::from("part 1"),
+        String::from("time"),
+        String::from("part 2"),
+        String::from("time"),
+    ]];
+    for (name, records) in results {
+        let part = |part: usize| {
+            records
+                .get(part)
+                .map_or((String::new(), String::new()), |record| {
+                    (cell(record), format!("{:.2?}", record.solve_time))
+                })
+        };
+        let ((part1, time1), (part2, time2)) = (part(0), part(1));
+        let parse = records
+            .first()
+            .map_or_else(String::new, |record| format!("{:.2?}", record.parse_time));
+        rows.push([name.clone(), parse, part1, time1, part2, time2]);
+    }
+
+    let mut widths = [0; 6];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut lines: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect();
+            String::from(cells.join("  ").trim_end())
+        })
+        .collect();
+
+    let failures: Vec<(&String, &Record)> = results
+        .iter()
+        .flat_map(|(name, records)| records.iter().map(move |record| (name, record)))
+        .filter(|(_, record)| record.status != Status::Ok)
+        .collect();
+    if !failures.is_empty() {
+        lines.push(String::new());
+        lines.push(format!("{} flagged:", failures.len()));
+        // only the first line of each error fits here, the whole of longer ones comes after
+        lines.extend(failures.iter().map(|(name, record)| {
+            let error = record.status.error().unwrap_or("");
+            format!(
+                "  {name} part {}: {} {}",
+                record.part,
+                record.status.name(),
+                error.lines().next().unwrap_or("")
+            )
+        }));
+    }
+    let mut reports: Vec<(&String, &str)> = failures
+        .iter()
+        .filter_map(|(name, record)| Some((*name, record.status.error()?)))
+        .filter(|(_, error)| error.contains('\n'))
+        .collect();
+    // both parts share the parse, so a malformed file would otherwise be reported twice
+    reports.dedup();
+    for (name, error) in reports {
+        lines.push(String::new());
+        lines.push(format!("{name}:"));
+        lines.extend(error.lines().map(|line| format!("  {line}")));
+    }
+    let timed_out = results
+        .iter()
+        .flat_map(|(_, records)| records)
+        .any(|record| matches!(record.status, Status::TimedOut(_)));
+    if timed_out {
+        lines.push(String::new());
+        lines.push(String::from(
+            "timed out solvers are cancelled but can run on for a while, timings after them may be skewed",
+        ));
+    }
+    lines
+}
+
+/// `compare <day> <dir>`, run a day over every input in a directory and print a table of the
+/// results. Each input gets `--timeout` seconds, or 10 without it. Exits with 1 if anything
+/// was flagged.
+pub fn run(args: &[String]) {
+    let (Some(day), Some(dir)) = (args.first(), args.get(1)) else {
+        eprintln!("usage: compare <day> <dir>");
+        process::exit(1);
+    };
+    let Some(day) = day.parse().ok().filter(|day| solution(*day).is_some()) else {
+        eprintln!("no solution for day {day:?}");
+        process::exit(1);
+    };
+
+    let timeout = progress::default_timeout().unwrap_or(TIMEOUT);
+    match compare(day, Path::new(dir), timeout) {
+        Ok(results) => {
+            for line in table(&results) {
+                println!("{line}");
+            }
+            let flagged = results
+                .iter()
+                .flat_map(|(_, records)| records)
+                .any(|record| record.status != Status::Ok);
+            if flagged {
+                process::exit(1);
+            }
+        }
+        Err(e) => {
+            eprintln!("compare day {day}: {e}");
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, thread, time::Duration};
+
+    use super::{compare, table};
+    use crate::{
+        common::{answer::Unsolved, progress::Canceller},
+        day8,
+        runner::record::measure_within,
+    };
+
+    #[test]
+    fn test_table_flags_failures() {
+        let dir = env::temp_dir().join(format!("aoc23-compare-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("alice"),
+            "32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483",
+        )
+        .unwrap();
+        fs::write(dir.join("bob"), "32T3K 4000000000\nKK677 4000000000").unwrap();
+        fs::write(dir.join("carol"), "32T3K").unwrap();
+
+        let results = compare(7, &dir, Duration::from_secs(10)).unwrap();
+        let names: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(vec!["alice", "bob", "carol"], names);
+
+        let lines = table(&results);
+        assert!(lines[0].starts_with("file   parse"));
+        let cells: Vec<&str> = lines[1].split_whitespace().collect();
+        assert_eq!(("alice", "6440", "5905"), (cells[0], cells[2], cells[4]));
+        assert!(lines[2].contains("[overflowed]"));
+        assert!(lines[3].contains("[malformed]"));
+        assert_eq!("4 flagged:", lines[5]);
+        assert_eq!("  bob part 1: overflowed overflowed u32", lines[6]);
+        assert_eq!(
+            "  carol part 1: malformed line 1, column 6: expected a space",
+            lines[8]
+        );
+        assert_eq!(
+            vec![
+                "",
+                "carol:",
+                "  line 1, column 6: expected a space",
+                "      32T3K",
+                "           ^"
+            ],
+            lines[10..]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// One ghost going round a ring of 2000 nodes, which part 2 only gives up on reaching a Z
+    /// node after walking every node at every point in a 2000 step path.
+    fn ring() -> String {
+        let name = |node: usize| format!("{node:04}{}", if node == 0 { 'A' } else { 'B' });
+        let mut input = format!("{}\n\n", "L".repeat(2000));
+        for node in 0..2000 {
+            input += &format!(
+                "{} = ({}, {})\n",
+                name(node),
+                name((node + 1) % 2000),
+                name(node)
+            );
+        }
+        input
+    }
+
+    #[test]
+    fn test_timeout_is_flagged() {
+        let records = measure_within(8, &ring(), Duration::from_millis(1));
+
+        assert_eq!(2, records.len());
+        assert_eq!("timed-out", records[1].status.name());
+        assert_eq!(None, records[1].answer);
+
+        let lines = table(&[(String::from("ring"), records)]);
+        assert!(lines
+            .last()
+            .unwrap()
+            .contains("timings after them may be skewed"));
+    }
+
+    #[test]
+    fn test_cancelled_solver_stops() {
+        let documents = day8::parse(&ring()).unwrap();
+        let solved = thread::spawn(move || {
+            let canceller = Canceller::new();
+            canceller.install();
+            canceller.cancel();
+            day8::part2(&documents)
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(
+            Err(Unsolved::NoAnswer(String::from(
+                "walk from 0000A cancelled after 4096 steps"
+            ))),
+            solved
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use std::{
+    any::Any,
+    fmt, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+pub mod compare;
+pub mod record;
+pub mod watch;
+
+use crate::{
+    common::answer::{Answer, Solved, Unsolved},
+    day1, day10, day2, day3, day4, day5, day6, day7, day8, day9,
+};
+
+/// What a day's `parse` hands to its parts, the type depends on the day.
+pub type Parsed = Box<dyn Any>;
+
+type Parser = Box<dyn Fn(&str) -> Result<Parsed, String>>;
+
+type Part = Box<dyn Fn(&dyn Any) -> Solved>;
+
+/// A day's parser and both of its parts. The parser runs once and each part takes what it
+/// produced, so the two can be timed separately.
+pub struct Solution {
+    pub day: u32,
+    parse: Parser,
+    parts: [Part; 2],
+}
+
+impl Solution {
+    pub fn new<T: 'static, E: fmt::Display + 'static>(
+        day: u32,
+        parse: fn(&str) -> Result<T, E>,
+        part1: fn(&T) -> Solved,
+        part2: fn(&T) -> Solved,
+    ) -> Solution {
+        let part = |solve: fn(&T) -> Solved| -> Part {
+            Box::new(move |parsed: &dyn Any| {
+                solve(
+                    parsed
+                        .downcast_ref()
+                        .expect("parsed input comes from the same day"),
+                )
+            })
+        };
+
+        Solution {
+            day,
+            parse: Box::new(move |input| match parse(input) {
+                Ok(parsed) => Ok(Box::new(parsed) as Parsed),
+                Err(e) => Err(e.to_string()),
+            }),
+            parts: [part(part1), part(part2)],
+        }
+    }
+
+    /// Parse the input for both parts, or say what was wrong with it.
+    pub fn parse(&self, input: &str) -> Result<Parsed, String> {
+        (self.parse)(input)
+    }
+
+    /// Solve part 1 or 2 from what `parse` produced, `None` for any other part.
+    pub fn solve(&self, part: u32, parsed: &dyn Any) -> Option<Solved> {
+        let solve = self.parts.get(part.checked_sub(1)? as usize)?;
+        Some(solve(parsed))
+    }
+
+    /// Parse the input and solve one part of it. Input that doesn't parse leaves the part
+    /// `Unsolved::Malformed`.
+    pub fn run(&self, part: u32, input: &str) -> Option<Solved> {
+        match self.parse(input) {
+            Ok(parsed) => self.solve(part, &*parsed),
+            Err(e) if (1..=2).contains(&part) => Some(Err(Unsolved::Malformed(e))),
+            Err(_) => None,
+        }
+    }
+}
+
+pub fn solutions() -> Vec<Solution> {
+    vec![
+        day1::solution(),
+        day2::solution(),
+        day3::solution(),
+        day4::solution(),
+        day5::solution(),
+        day6::solution(),
+        day7::solution(),
+        day8::solution(),
+        day9::solution(),
+        day10::solution(),
+    ]
+}
+
+pub fn solution(day: u32) -> Option<Solution> {
+    solutions().into_iter().find(|solution| solution.day == day)
+}
+
+/// Where the example inputs live, `examples/day<N>/<name>.txt` with the answers they should give
+/// in `examples/day<N>/answers.txt`.
+fn examples_dir(day: u32) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join(format!("day{day}"))
+}
+
+/// Load one of a day's example inputs. Trailing newlines are dropped since the solvers expect
+/// the last line to end the file.
+pub fn example(day: u32, name: &str) -> String {
+    example_in(&examples_dir(day), name)
+}
+
+fn example_in(dir: &Path, name: &str) -> String {
+    let path = dir.join(format!("{name}.txt"));
+    let contents = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("failed to read {}: {e}", path.display()));
+    String::from(contents.trim_end_matches(['\n', '\r']))
+}
+
+/// One line of an answers file: `<example> <part> <answer>`.
+#[derive(Debug, PartialEq)]
+pub struct ExpectedAnswer {
+    pub example: String,
+    pub part: u32,
+    pub answer: Answer,
+}
+
+impl fmt::Display for ExpectedAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} part {}", self.example, self.part)
+    }
+}
+
+/// Parse an answers file. Blank lines and lines starting with `#` are skipped.
+pub fn parse_answers(input: &str) -> Result<Vec<ExpectedAnswer>, String> {
+    let mut answers = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [example, part, answer] = fields[..] else {
+            return Err(format!(
+                "line {}: expected \"<example> <part> <answer>\" but found {line:?}",
+                index + 1
+            ));
+        };
+        let part = match part {
+            "1" => 1,
+            "2" => 2,
+            _ => return Err(format!("line {}: {part:?} is not part 1 or 2", index + 1)),
+        };
+
+        let Ok(answer) = answer.parse();
+        answers.push(ExpectedAnswer {
+            example: String::from(example),
+            part,
+            answer,
+        });
+    }
+
+    Ok(answers)
+}
+
+/// Run a day against every one of its examples and return what went wrong, if anything. An
+/// example with no answer listed counts as a failure so new ones can't be silently ignored,
+/// unless answers.txt lists nothing at all, as for a day just made by `new-day`.
+pub fn check_examples(day: u32) -> Result<usize, Vec<String>> {
+    let solution = solution(day).ok_or_else(|| vec![format!("day {day} has no solution")])?;
+    check_examples_in(&solution, &examples_dir(day))
+}
+
+/// `check_examples` for the examples in `dir`.
+fn check_examples_in(solution: &Solution, dir: &Path) -> Result<usize, Vec<String>> {
+    let day = solution.day;
+    let answers = fs::read_to_string(dir.join("answers.txt"))
+        .map_err(|e| vec![format!("failed to read answers for day {day}: {e}")])
+        .and_then(|contents| parse_answers(&contents).map_err(|e| vec![e]))?;
+    if answers.is_empty() {
+        return Ok(0);
+    }
+
+    let mut failures = Vec::new();
+
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map_err(|e| vec![format!("failed to read {}: {e}", dir.display())])?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            Some(String::from(name.strip_suffix(".txt")?))
+        })
+        .filter(|name| name != "answers")
+        .collect();
+    names.sort();
+
+    for name in &names {
+        if !answers.iter().any(|expected| &expected.example == name) {
+            failures.push(format!("{name} has no answers listed"));
+        }
+    }
+
+    for expected in &answers {
+        if !names.contains(&expected.example) {
+            failures.push(format!("{expected}: no such example"));
+            continue;
+        }
+
+        let solved = solution
+            .run(expected.part, &example_in(dir, &expected.example))
+            .expect("answers only hold parts 1 and 2");
+        match solved {
+            Ok(answer) if answer == expected.answer => {}
+            Ok(answer) => failures.push(format!(
+                "{expected}: expected {} but got {answer}",
+                expected.answer
+            )),
+            Err(unsolved) => failures.push(format!("{expected}: {unsolved}")),
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(answers.len())
+    } else {
+        Err(failures)
+    }
+}
+
+/// Run both parts of a day against its puzzle input and print one JSON record per part.
+pub fn print_json(day: &str) {
+    let Some(solution) = day.parse().ok().and_then(solution) else {
+        eprintln!("no solution for day {day:?}");
+        process::exit(1);
+    };
+
+    let file_path = format!("input/day{}-input", solution.day);
+    let input = fs::read_to_string(&file_path).unwrap_or_else(|e| {
+        eprintln!("failed to read {file_path}: {e}");
+        process::exit(1);
+    });
+
+    for record in record::measure(&solution, &input) {
+        println!("{}", record.to_json());
+    }
+}
+
+/// `examples [day]`, check one day or every day against its examples and list what failed.
+pub fn run_examples(day: Option<&str>) {
+    let days: Vec<u32> = match day {
+        Some(day) => match day.parse() {
+            Ok(day) => vec![day],
+            Err(_) => {
+                eprintln!("{day:?} is not a day");
+                process::exit(1);
+            }
+        },
+        None => solutions().iter().map(|solution| solution.day).collect(),
+    };
+
+    let mut all_passed = true;
+    for day in days {
+        match check_examples(day) {
+            Ok(0) => println!("day {day}: no answers listed yet"),
+            Ok(count) => println!("day {day}: {count} answers ok"),
+            Err(failures) => {
+                all_passed = false;
+                println!("day {day}: failed");
+                for failure in failures {
+                    println!("  {failure}");
+                }
+            }
+        }
+    }
+
+    if !all_passed {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use super::{check_examples, check_examples_in, parse_answers, solution, ExpectedAnswer};
+    use crate::{common::answer::Answer, scaffold::new_day};
+
+    /// A day straight out of `new-day` has nothing to check until its answers are listed. The
+    /// new day's module isn't compiled here, so day 1 stands in for it.
+    #[test]
+    fn test_new_day_examples_pass() {
+        let root = env::temp_dir().join(format!("aoc23-new-day-examples-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let crate_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        for file in ["src/lib.rs", "src/runner/mod.rs", "src/main.rs"] {
+            fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            fs::copy(crate_root.join(file), root.join(file)).unwrap();
+        }
+        new_day(&root, 11).unwrap();
+        let examples = root.join("examples/day11");
+        let stand_in = solution(1).unwrap();
+
+        assert_eq!(Ok(0), check_examples_in(&stand_in, &examples));
+
+        fs::write(examples.join("answers.txt"), "example 1 12\n").unwrap();
+        assert_eq!(
+            Err(vec![String::from("example part 1: expected 12 but got 0")]),
+            check_examples_in(&stand_in, &examples)
+        );
+        fs::write(examples.join("example.txt"), "1abc2\n").unwrap();
+        assert_eq!(Ok(1), check_examples_in(&stand_in, &examples));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        assert_eq!(
+            vec![ExpectedAnswer {
+                example: String::from("example"),
+                part: 2,
+                answer: Answer::Signed(-7),
+            }],
+            parse_answers("# comment\n\nexample 2 -7\n").unwrap()
+        );
+        assert_eq!(
+            Err(String::from("line 1: \"3\" is not part 1 or 2")),
+            parse_answers("example 3 10")
+        );
+        assert!(parse_answers("example 1").is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    common::{
+        answer::{Answer, Unsolved},
+        maths::fnv1a,
+        progress::Canceller,
+    },
+    runner::{self, Solution},
+};
+
+/// How solving a part went.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Ok,
+    // the parser or the part panicked, with the panic message
+    Panicked(String),
+    // the part's arithmetic didn't fit, with what overflowed
+    Overflowed(String),
+    // the input didn't parse, with what the parser expected
+    Malformed(String),
+    // the input parsed but has no answer, with why
+    NoAnswer(String),
+    // no answer came back in time, with how long was allowed
+    TimedOut(String),
+}
+
+impl Status {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Panicked(_) => "panicked",
+            Status::Overflowed(_) => "overflowed",
+            Status::Malformed(_) => "malformed",
+            Status::NoAnswer(_) => "no-answer",
+            Status::TimedOut(_) => "timed-out",
+        }
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            Status::Ok => None,
+            Status::Panicked(message)
+            | Status::Overflowed(message)
+            | Status::Malformed(message)
+            | Status::NoAnswer(message)
+            | Status::TimedOut(message) => Some(message),
+        }
+    }
+}
+
+/// The outcome of running one part against one input.
+#[derive(Debug)]
+pub struct Record {
+    pub day: u32,
+    pub part: u32,
+    pub answer: Option<Answer>,
+    // both parts share one parse so they report the same parse time
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+    pub input_hash: u64,
+    pub status: Status,
+}
+
+impl Record {
+    /// One JSON object on a single line. Every field is always present, `answer` and `error`
+    /// are null when they don't apply, times are whole nanoseconds and the input hash is 64 bit
+    /// FNV-1a as 16 hex digits.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"day\":{},\"part\":{},\"status\":{},\"answer\":{},\"error\":{},\"parse_time_ns\":{},\"solve_time_ns\":{},\"input_hash\":\"{:016x}\"}}",
+            self.day,
+            self.part,
+            json_string(self.status.name()),
+            self.answer
+                .as_ref()
+                .map_or(String::from("null"), |answer| json_string(&answer.to_string())),
+            self.status.error().map_or(String::from("null"), json_string),
+            self.parse_time.as_nanos(),
+            self.solve_time.as_nanos(),
+            self.input_hash,
+        )
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        String::from(*message)
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("panicked")
+    }
+}
+
+/// Parse the input once, then solve both parts, timing each step. A panic in one part is
+/// recorded against that part and doesn't stop the other; a panic or parse error fails both.
+pub fn measure(solution: &Solution, input: &str) -> Vec<Record> {
+    records(solution, input).collect()
+}
+
+/// `measure` with a limit on how long the whole input can take, for inputs that might send a
+/// solver into an endless loop. Any part without an answer by then is recorded as timed out.
+/// The solver runs on its own thread and is cancelled when time runs out, which stops any
+/// search it's in at its next `Progress` step. Work that doesn't step a `Progress` runs on until
+/// it finishes.
+pub fn measure_within(day: u32, input: &str, timeout: Duration) -> Vec<Record> {
+    let (sender, receiver) = mpsc::channel();
+    let owned = String::from(input);
+    let canceller = Canceller::new();
+    let installed = canceller.clone();
+    thread::spawn(move || {
+        installed.install();
+        if let Some(solution) = runner::solution(day) {
+            for record in records(&solution, &owned) {
+                if sender.send(record).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    let deadline = Instant::now() + timeout;
+    let mut measured: Vec<Record> = Vec::new();
+    while measured.len() < 2 {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(left) {
+            Ok(record) => measured.push(record),
+            Err(RecvTimeoutError::Timeout) => {
+                canceller.cancel();
+                break;
+            }
+            Err(RecvTimeoutError::Disconnected) => return measured,
+        }
+    }
+
+    let parse_time = measured.first().map_or(timeout, |record| record.parse_time);
+    for part in measured.len() as u32 + 1..=2 {
+        measured.push(Record {
+            day,
+            part,
+            answer: None,
+            parse_time,
+            solve_time: timeout.saturating_sub(parse_time),
+            input_hash: fnv1a(input.as_bytes()),
+            status: Status::TimedOut(format!("no answer within {timeout:?}")),
+        });
+    }
+    measured
+}
+
+/// The records `measure` returns, each worked out as it's asked for.
+fn records<'a>(solution: &'a Solution, input: &'a str) -> impl Iterator<Item = Record> + 'a {
+    let input_hash = fnv1a(input.as_bytes());
+
+    let now = Instant::now();
+    let parsed = panic::catch_unwind(AssertUnwindSafe(|| solution.parse(input)))
+        .map_err(|payload| Status::Panicked(panic_message(payload)))
+        .and_then(|parsed| parsed.map_err(Status::Malformed));
+    let parse_time = now.elapsed();
+
+    [1, 2].into_iter().map(move |part| {
+        let now = Instant::now();
+        let (answer, status) = match &parsed {
+            Ok(parsed) => {
+                let solved = panic::catch_unwind(AssertUnwindSafe(|| {
+                    solution.solve(part, &**parsed).transpose()
+                }));
+                match solved {
+                    Ok(Ok(answer)) => (answer, Status::Ok),
+                    Ok(Err(Unsolved::Overflow(overflow))) => {
+                        (None, Status::Overflowed(overflow.to_string()))
+                    }
+                    Ok(Err(Unsolved::NoAnswer(why))) => (None, Status::NoAnswer(why)),
+                    Ok(Err(Unsolved::Malformed(e))) => (None, Status::Malformed(e)),
+                    Err(payload) => (None, Status::Panicked(panic_message(payload))),
+                }
+            }
+            Err(status) => (None, status.clone()),
+        };
+
+        Record {
+            day: solution.day,
+            part,
+            answer,
+            parse_time,
+            solve_time: now.elapsed(),
+            input_hash,
+            status,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{measure, Record, Status};
+    use crate::{
+        common::answer::Answer,
+        runner::{example, solution},
+    };
+
+    #[test]
+    fn test_json_schema() {
+        let record = Record {
+            day: 5,
+            part: 1,
+            answer: Some(Answer::Unsigned(35)),
+            parse_time: Duration::from_micros(12),
+            solve_time: Duration::from_nanos(345),
+            input_hash: 0xbeef,
+            status: Status::Ok,
+        };
+        assert_eq!(
+            r#"{"day":5,"part":1,"status":"ok","answer":"35","error":null,"parse_time_ns":12000,"solve_time_ns":345,"input_hash":"000000000000beef"}"#,
+            record.to_json()
+        );
+
+        let record = Record {
+            answer: None,
+            status: Status::Panicked(String::from("no \"AAA\"\nnode")),
+            ..record
+        };
+        assert_eq!(
+            r#"{"day":5,"part":1,"status":"panicked","answer":null,"error":"no \"AAA\"\nnode","parse_time_ns":12000,"solve_time_ns":345,"input_hash":"000000000000beef"}"#,
+            record.to_json()
+        );
+    }
+
+    #[test]
+    fn test_measure_both_parts() {
+        let input = example(7, "example");
+        let records = measure(&solution(7).unwrap(), &input);
+
+        let answers: Vec<(u32, Option<String>, &Status)> = records
+            .iter()
+            .map(|record| {
+                let answer = record.answer.as_ref().map(|answer| answer.to_string());
+                (record.part, answer, &record.status)
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (1, Some(String::from("6440")), &Status::Ok),
+                (2, Some(String::from("5905")), &Status::Ok)
+            ],
+            answers
+        );
+        assert_eq!(records[0].input_hash, records[1].input_hash);
+        assert_eq!(records[0].parse_time, records[1].parse_time);
+    }
+
+    #[test]
+    fn test_no_answer_only_fails_its_part() {
+        // the ghost example has no AAA node for part 1 to start from
+        let input = example(8, "ghosts");
+        let records = measure(&solution(8).unwrap(), &input);
+
+        assert_eq!("no-answer", records[0].status.name());
+        assert_eq!(None, records[0].answer);
+        assert_eq!(Status::Ok, records[1].status);
+        assert_eq!(Some(Answer::Unsigned(6)), records[1].answer);
+    }
+
+    #[test]
+    fn test_overflow_is_reported() {
+        let input = "32T3K 4000000000\nKK677 4000000000";
+        let records = measure(&solution(7).unwrap(), input);
+
+        assert_eq!(
+            Status::Overflowed(String::from("overflowed u32")),
+            records[0].status
+        );
+        assert_eq!(None, records[0].answer);
+        assert!(records[0].to_json().contains(r#""status":"overflowed""#));
+    }
+
+    #[test]
+    fn test_parse_error_fails_both_parts() {
+        let records = measure(&solution(7).unwrap(), "32T3K");
+
+        assert_eq!(2, records.len());
+        for record in &records {
+            assert_eq!("malformed", record.status.name());
+            assert!(record.status.error().unwrap().contains("line 1, column 6"));
+        }
+    }
+}
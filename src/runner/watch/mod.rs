@@ -0,0 +1,209 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process, thread,
+    time::Duration,
+};
+
+use crate::{
+    common::maths::fnv1a,
+    runner::{
+        examples_dir,
+        record::{measure, Record},
+        solution, Solution,
+    },
+};
+
+const POLL: Duration = Duration::from_millis(500);
+
+/// A hash of each watched file's contents, `None` while it doesn't exist. Inputs are small
+/// enough to read every poll, and unlike modification times this can't miss a quick edit.
+type Stamps = BTreeMap<PathBuf, Option<u64>>;
+
+/// The puzzle input and every example fixture for `day`, relative to `root`.
+fn inputs(root: &Path, day: u32) -> Vec<(String, PathBuf)> {
+    let mut inputs = Vec::new();
+
+    let dir = root.join("examples").join(format!("day{day}"));
+    if let Ok(entries) = fs::read_dir(&dir) {
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter_map(|name| Some(String::from(name.strip_suffix(".txt")?)))
+            .filter(|name| name != "answers")
+            .collect();
+        names.sort();
+        inputs.extend(names.into_iter().map(|name| {
+            let path = dir.join(format!("{name}.txt"));
+            (name, path)
+        }));
+    }
+
+    inputs.push((
+        String::from("input"),
+        root.join("input").join(format!("day{day}-input")),
+    ));
+    inputs
+}
+
+fn stamp(paths: impl IntoIterator<Item = PathBuf>) -> Stamps {
+    paths
+        .into_iter()
+        .map(|path| {
+            let stamp = fs::read(&path).ok().map(|contents| fnv1a(&contents));
+            (path, stamp)
+        })
+        .collect()
+}
+
+/// Both parts of `solution` against every input that has something in it, by input name.
+/// Examples drop their trailing newlines the same way `example` does.
+pub fn run_all(solution: &Solution, inputs: &[(String, PathBuf)]) -> Vec<(String, Vec<Record>)> {
+    inputs
+        .iter()
+        .filter_map(|(name, path)| {
+            let input = fs::read_to_string(path).ok()?;
+            let input = if name == "input" {
+                input.as_str()
+            } else {
+                input.trim_end_matches(['\n', '\r'])
+            };
+            if input.trim().is_empty() {
+                return None;
+            }
+            Some((name.clone(), measure(solution, input)))
+        })
+        .collect()
+}
+
+fn outcome(record: &Record) -> String {
+    match (&record.answer, record.status.error()) {
+        (Some(answer), _) => answer.to_string(),
+        (None, Some(error)) => format!("{} ({error})", record.status.name()),
+        (None, None) => String::from("no answer"),
+    }
+}
+
+/// One line per input and part giving the answer and timings, marked with what it was before
+/// whenever it differs from `previous`.
+pub fn report(
+    previous: &BTreeMap<(String, u32), String>,
+    runs: &[(String, Vec<Record>)],
+) -> Vec<String> {
+    let width = runs.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+
+    runs.iter()
+        .flat_map(|(name, records)| records.iter().map(move |record| (name, record)))
+        .map(|(name, record)| {
+            let outcome = outcome(record);
+            let change = match previous.get(&(name.clone(), record.part)) {
+                Some(before) if *before == outcome => String::new(),
+                Some(before) => format!("  (was {before})"),
+                None if previous.is_empty() => String::new(),
+                None => String::from("  (new)"),
+            };
+            format!(
+                "{name:<width$}  part {}: {outcome}  parse {:.2?} solve {:.2?}{change}",
+                record.part, record.parse_time, record.solve_time
+            )
+        })
+        .collect()
+}
+
+fn outcomes(runs: &[(String, Vec<Record>)]) -> BTreeMap<(String, u32), String> {
+    runs.iter()
+        .flat_map(|(name, records)| {
+            records
+                .iter()
+                .map(move |record| ((name.clone(), record.part), outcome(record)))
+        })
+        .collect()
+}
+
+/// `watch <day>`, re-run the day whenever its input or examples change. The solver is what was
+/// compiled in, so a change to the day's source only gets a reminder to restart.
+pub fn run(args: &[String]) {
+    let Some(solution) = args
+        .first()
+        .and_then(|day| day.parse().ok())
+        .and_then(solution)
+    else {
+        eprintln!("usage: watch <day>, for a day that has a solution");
+        process::exit(1);
+    };
+    let day = solution.day;
+
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let source = root.join("src").join(format!("day{day}")).join("mod.rs");
+    let mut source_stamp = stamp([source.clone()]);
+    let mut stamps = Stamps::new();
+    let mut previous = BTreeMap::new();
+
+    println!(
+        "watching input/day{day}-input and {}, ctrl-c to stop",
+        examples_dir(day).display()
+    );
+    loop {
+        let watched = inputs(&root, day);
+        let latest = stamp(watched.iter().map(|(_, path)| path.clone()));
+        if latest != stamps {
+            stamps = latest;
+            let runs = run_all(&solution, &watched);
+            println!("\nday {day}:");
+            for line in report(&previous, &runs) {
+                println!("  {line}");
+            }
+            previous = outcomes(&runs);
+        }
+
+        let latest = stamp([source.clone()]);
+        if latest != source_stamp {
+            source_stamp = latest;
+            println!("\n{} changed, restart watch to build it", source.display());
+        }
+
+        thread::sleep(POLL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, env, fs};
+
+    use super::{inputs, outcomes, report, run_all, stamp};
+    use crate::runner::solution;
+
+    #[test]
+    fn test_rerun_after_input_change() {
+        let root = env::temp_dir().join(format!("aoc23-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("examples/day9")).unwrap();
+        fs::write(root.join("examples/day9/example.txt"), "0 3 6 9 12 15\n").unwrap();
+        fs::write(root.join("examples/day9/answers.txt"), "example 1 18\n").unwrap();
+
+        let solution = solution(9).unwrap();
+        let watched = inputs(&root, 9);
+        assert_eq!(
+            vec!["example", "input"],
+            watched.iter().map(|(name, _)| name).collect::<Vec<_>>()
+        );
+        let before = stamp(watched.iter().map(|(_, path)| path.clone()));
+
+        // no puzzle input yet, so only the example runs
+        let runs = run_all(&solution, &watched);
+        assert_eq!(1, runs.len());
+        let first = report(&BTreeMap::new(), &runs);
+        assert!(first[0].starts_with("example  part 1: 18  parse "));
+        assert!(!first[0].contains("was"));
+
+        fs::write(root.join("examples/day9/example.txt"), "1 3 6 10 15 21\n").unwrap();
+        assert_ne!(before, stamp(watched.iter().map(|(_, path)| path.clone())));
+
+        let previous = outcomes(&runs);
+        let lines = report(&previous, &run_all(&solution, &watched));
+        assert!(lines[0].starts_with("example  part 1: 28  "));
+        assert!(lines[0].ends_with("(was 18)"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,345 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+/// The module a new day starts from. Its model is just the lines of the input and both parts
+/// count them, so it builds and runs straight away.
+const TEMPLATE: &str = r#"use std::convert::Infallible;
+
+use crate::{
+    common::{
+        answer::{Answer, Solved},
+        file_io::{read_file_to_string, split_lines},
+    },
+    runner::Solution,
+};
+
+pub fn puz1() {
+    let input = read_file_to_string("input/day{N}-input");
+    let Ok(lines) = parse(&input);
+    match part1(&lines) {
+        Ok(answer) => println!("day{N} puz1: {answer}"),
+        Err(unsolved) => println!("day{N} puz1 {unsolved}"),
+    }
+}
+
+pub fn puz2() {
+    let input = read_file_to_string("input/day{N}-input");
+    let Ok(lines) = parse(&input);
+    match part2(&lines) {
+        Ok(answer) => println!("day{N} puz2: {answer}"),
+        Err(unsolved) => println!("day{N} puz2 {unsolved}"),
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<String>, Infallible> {
+    Ok(split_lines(input))
+}
+
+pub fn part1(lines: &[String]) -> Solved {
+    Ok(Answer::from(lines.len()))
+}
+
+pub fn part2(lines: &[String]) -> Solved {
+    Ok(Answer::from(lines.len()))
+}
+
+pub fn solution() -> Solution {
+    Solution::new({N}, parse, |lines| part1(lines), |lines| part2(lines))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runner::check_examples;
+
+    #[test]
+    fn test_examples() {
+        if let Err(failures) = check_examples({N}) {
+            panic!("day {N} examples failed:\n  {}", failures.join("\n  "));
+        }
+    }
+}
+"#;
+
+const ANSWERS: &str = "# <example> <part> <answer>\n";
+
+/// `new-day <N>`, create day N's module, input and example files and register it everywhere a
+/// day has to be listed.
+pub fn run(args: &[String]) {
+    let Some(day) = args.first() else {
+        eprintln!("usage: new-day <N>");
+        process::exit(1);
+    };
+
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    match day.parse().map_err(|_| format!("{day:?} is not a day")) {
+        Ok(day) => match new_day(&root, day) {
+            Ok(created) => {
+                for path in created {
+                    println!("created {}", path.display());
+                }
+                println!("registered day {day} in src/lib.rs, src/runner/mod.rs and src/main.rs");
+            }
+            Err(e) => {
+                eprintln!("new-day {day}: {e}");
+                process::exit(1);
+            }
+        },
+        Err(e) => {
+            eprintln!("new-day: {e}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Scaffold day `day` in the crate at `root` and return the files created. Nothing is written
+/// unless every registration can be made, and an existing day is never touched.
+pub fn new_day(root: &Path, day: u32) -> Result<Vec<PathBuf>, String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("there is no day {day}, days run from 1 to 25"));
+    }
+
+    let module = root.join("src").join(format!("day{day}")).join("mod.rs");
+    let input = root.join("input").join(format!("day{day}-input"));
+    let examples = root.join("examples").join(format!("day{day}"));
+    for path in [&module, &input, &examples] {
+        if path.exists() {
+            return Err(format!("{} already exists", path.display()));
+        }
+    }
+
+    let read = |path: &str| {
+        fs::read_to_string(root.join(path)).map_err(|e| format!("failed to read {path}: {e}"))
+    };
+    let lib = register_module(&read("src/lib.rs")?, day)?;
+    let runner = register_solution(&read("src/runner/mod.rs")?, day)?;
+    let main = register_command(&read("src/main.rs")?, day)?;
+
+    let write = |path: &Path, contents: &str| {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("failed to create {}: {e}", dir.display()))?;
+        }
+        fs::write(path, contents).map_err(|e| format!("failed to write {}: {e}", path.display()))
+    };
+    let created = vec![
+        module,
+        input,
+        examples.join("example.txt"),
+        examples.join("answers.txt"),
+    ];
+    write(&created[0], &TEMPLATE.replace("{N}", &day.to_string()))?;
+    write(&created[1], "")?;
+    write(&created[2], "")?;
+    write(&created[3], ANSWERS)?;
+
+    write(&root.join("src/lib.rs"), &lib)?;
+    write(&root.join("src/runner/mod.rs"), &runner)?;
+    write(&root.join("src/main.rs"), &main)?;
+
+    Ok(created)
+}
+
+/// Insert `line` after the last line of `source` that `after` matches.
+fn insert_after(source: &str, after: impl Fn(&str) -> bool, line: &str) -> Option<String> {
+    let lines: Vec<&str> = source.lines().collect();
+    let index = lines.iter().rposition(|existing| after(existing))?;
+
+    let mut lines: Vec<String> = lines.iter().map(|line| String::from(*line)).collect();
+    lines.insert(index + 1, String::from(line));
+    Some(lines.join("\n") + "\n")
+}
+
+/// rustfmt's line width, which the rewrapped `use` lists have to stay inside.
+const MAX_WIDTH: usize = 100;
+
+/// True for a bare name such as `day1` or `generate`, rather than a path or a nested list.
+fn is_name(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The names on a line holding nothing but a comma separated run of them, the way rustfmt
+/// leaves the tail of a `use` list too long for one line.
+fn names(line: &str) -> Option<Vec<String>> {
+    let names: Vec<String> = line
+        .trim()
+        .strip_suffix(',')?
+        .split(", ")
+        .map(String::from)
+        .collect();
+    names.iter().all(|name| is_name(name)).then_some(names)
+}
+
+/// Add `dayN` to the `use` list that imports `day1`, in the order rustfmt sorts the names and
+/// with the lines they sit on rewrapped the way rustfmt would wrap them.
+fn add_import(source: &str, day: u32) -> Option<String> {
+    let mut lines: Vec<String> = source.lines().map(String::from).collect();
+    let has_day1 = |names: &[String]| names.iter().any(|name| name == "day1");
+
+    // the names of a list that fits on its `use` line
+    let inline = |line: &str| {
+        let (path, list) = line.strip_prefix("use ")?.split_once('{')?;
+        Some((path.to_string(), names(&list.replace("};", ","))?))
+    };
+
+    if let Some(index) = lines
+        .iter()
+        .position(|line| inline(line).is_some_and(|(_, names)| has_day1(&names)))
+    {
+        let (path, mut imported) = inline(&lines[index])?;
+        imported.push(format!("day{day}"));
+        imported.sort();
+        lines[index] = format!("use {path}{{{}}};", imported.join(", "));
+    } else {
+        let first = lines
+            .iter()
+            .position(|line| names(line).is_some_and(|names| has_day1(&names)))?;
+        let start = (0..first)
+            .rev()
+            .take_while(|index| names(&lines[*index]).is_some())
+            .last()
+            .unwrap_or(first);
+        let end = (first..lines.len())
+            .take_while(|index| names(&lines[*index]).is_some())
+            .last()?;
+
+        let indent: String = lines[start]
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect();
+        let mut imported: Vec<String> = lines[start..=end]
+            .iter()
+            .filter_map(|line| names(line))
+            .flatten()
+            .collect();
+        imported.push(format!("day{day}"));
+        imported.sort();
+
+        let mut wrapped: Vec<String> = Vec::new();
+        for name in imported {
+            let next = format!(" {name},");
+            match wrapped.last_mut() {
+                Some(line) if line.len() + next.len() <= MAX_WIDTH => *line += &next,
+                _ => wrapped.push(format!("{indent}{name},")),
+            }
+        }
+        lines.splice(start..=end, wrapped);
+    }
+
+    Some(lines.join("\n") + "\n")
+}
+
+fn register_module(lib: &str, day: u32) -> Result<String, String> {
+    if lib.lines().any(|line| line == format!("pub mod day{day};")) {
+        return Err(format!("day{day} is already declared in src/lib.rs"));
+    }
+    // rustfmt keeps the modules sorted by name
+    let name = format!("day{day}");
+    insert_after(
+        lib,
+        |line| {
+            line.strip_prefix("pub mod ")
+                .and_then(|line| line.strip_suffix(';'))
+                .is_some_and(|module| module < name.as_str())
+        },
+        &format!("pub mod {name};"),
+    )
+    .ok_or_else(|| String::from("src/lib.rs declares no modules to add this one after"))
+}
+
+fn register_solution(runner: &str, day: u32) -> Result<String, String> {
+    let runner = add_import(runner, day)
+        .ok_or_else(|| String::from("src/runner/mod.rs doesn't import the days"))?;
+    insert_after(
+        &runner,
+        |line| line.trim_start().starts_with("day") && line.ends_with("::solution(),"),
+        &format!("        day{day}::solution(),"),
+    )
+    .ok_or_else(|| String::from("src/runner/mod.rs has no list of solutions"))
+}
+
+fn register_command(main: &str, day: u32) -> Result<String, String> {
+    let main =
+        add_import(main, day).ok_or_else(|| String::from("src/main.rs doesn't import the days"))?;
+    let arm = format!(
+        "        \"{day}\" => {{\n            day{day}::puz1();\n            day{day}::puz2();\n        }}"
+    );
+    let index = main
+        .find("        _ => {}")
+        .ok_or_else(|| String::from("src/main.rs has no match on the day"))?;
+    Ok(format!("{}{arm}\n{}", &main[..index], &main[index..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use super::{new_day, register_command, register_module, register_solution};
+
+    #[test]
+    fn test_register_module() {
+        let lib = "pub mod common;\npub mod day1;\npub mod day2;\npub mod runner;\n";
+
+        assert_eq!(
+            Ok(String::from(
+                "pub mod common;\npub mod day1;\npub mod day11;\npub mod day2;\npub mod runner;\n"
+            )),
+            register_module(lib, 11)
+        );
+        assert_eq!(
+            Err(String::from("day2 is already declared in src/lib.rs")),
+            register_module(lib, 2)
+        );
+    }
+
+    #[test]
+    fn test_register_solution_and_command() {
+        let runner = "use crate::{day1, day2};\n\nfn solutions() {\n    vec![\n        day1::solution(),\n        day2::solution(),\n    ]\n}\n";
+        let registered = register_solution(runner, 3).unwrap();
+        assert!(registered.starts_with("use crate::{day1, day2, day3};"));
+        assert!(registered.contains("        day2::solution(),\n        day3::solution(),\n    ]"));
+
+        let main = "use aoc23_1::{day1, generate, runner};\n\nfn main() {\n    match day {\n        \"1\" => day1::puz1(),\n        _ => {}\n    }\n}\n";
+        let registered = register_command(main, 3).unwrap();
+        assert!(registered.starts_with("use aoc23_1::{day1, day3, generate, runner};"));
+        assert!(registered.contains(
+            "        \"3\" => {\n            day3::puz1();\n            day3::puz2();\n        }\n        _ => {}"
+        ));
+    }
+
+    /// Scaffold into a copy of this crate's registration files so the real tree is left alone.
+    #[test]
+    fn test_new_day_refuses_to_overwrite() {
+        let root = env::temp_dir().join(format!("aoc23-new-day-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let crate_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        for file in ["src/lib.rs", "src/runner/mod.rs", "src/main.rs"] {
+            fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            fs::copy(crate_root.join(file), root.join(file)).unwrap();
+        }
+
+        let created = new_day(&root, 11).unwrap();
+        assert_eq!(4, created.len());
+        let module = fs::read_to_string(root.join("src/day11/mod.rs")).unwrap();
+        assert!(module.contains("read_file_to_string(\"input/day11-input\")"));
+        assert!(module.contains("check_examples(11)"));
+        let lib = fs::read_to_string(root.join("src/lib.rs")).unwrap();
+        assert!(lib.contains("pub mod day11;"));
+        let main = fs::read_to_string(root.join("src/main.rs")).unwrap();
+        assert!(main.contains(
+            "    day1, day10, day11, day2, day3, day4, day5, day6, day7, day8, day9, fetch, generate, repl,\n    runner, scaffold, submit,\n"
+        ));
+
+        let lib_before = lib.clone();
+        assert!(new_day(&root, 11).unwrap_err().contains("already exists"));
+        assert_eq!(
+            lib_before,
+            fs::read_to_string(root.join("src/lib.rs")).unwrap()
+        );
+        assert!(new_day(&root, 26).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
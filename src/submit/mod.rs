@@ -0,0 +1,549 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    common::{answer::Answer, file_io::read_file_to_string},
+    fetch::{Fetcher, USER_AGENT},
+    info, runner,
+};
+
+/// What the puzzle site made of an answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+    // answered too soon after the last one, nothing was judged
+    TooSoon,
+}
+
+impl Verdict {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Right => "right",
+            Verdict::Wrong => "wrong",
+            Verdict::TooHigh => "too-high",
+            Verdict::TooLow => "too-low",
+            Verdict::TooSoon => "too-soon",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Verdict> {
+        [
+            Verdict::Right,
+            Verdict::Wrong,
+            Verdict::TooHigh,
+            Verdict::TooLow,
+            Verdict::TooSoon,
+        ]
+        .into_iter()
+        .find(|verdict| verdict.name() == name)
+    }
+}
+
+/// One answer sent for a part and what came back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    // seconds since the epoch
+    pub at: u64,
+    // how long the site asked us to wait before answering again
+    pub wait: u64,
+    pub verdict: Verdict,
+    pub answer: Answer,
+}
+
+impl fmt::Display for Attempt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.at,
+            self.wait,
+            self.verdict.name(),
+            self.answer
+        )
+    }
+}
+
+/// Read back the attempts written by `record`, one per line as `at wait verdict answer`.
+pub fn parse_attempts(input: &str) -> Result<Vec<Attempt>, String> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            let bad = || format!("line {}: expected `at wait verdict answer`", index + 1);
+            let mut fields = line.splitn(4, ' ');
+            let at = fields
+                .next()
+                .and_then(|at| at.parse().ok())
+                .ok_or_else(bad)?;
+            let wait = fields
+                .next()
+                .and_then(|wait| wait.parse().ok())
+                .ok_or_else(bad)?;
+            let verdict = fields.next().and_then(Verdict::from_name).ok_or_else(bad)?;
+            let Ok(answer) = fields.next().ok_or_else(bad)?.parse();
+
+            Ok(Attempt {
+                at,
+                wait,
+                verdict,
+                answer,
+            })
+        })
+        .collect()
+}
+
+/// Why an answer wasn't sent.
+#[derive(Debug, PartialEq)]
+pub enum Refusal {
+    Solved(Answer),
+    AlreadyJudged(Verdict),
+    TooHigh(Answer),
+    TooLow(Answer),
+    Wait(u64),
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Refusal::Solved(answer) => write!(f, "already solved, the answer was {answer}"),
+            Refusal::AlreadyJudged(verdict) => {
+                write!(f, "this answer was already judged {}", verdict.name())
+            }
+            Refusal::TooHigh(bound) => write!(f, "{bound} was already too high"),
+            Refusal::TooLow(bound) => write!(f, "{bound} was already too low"),
+            Refusal::Wait(seconds) => write!(f, "the site asked us to wait another {seconds}s"),
+        }
+    }
+}
+
+fn number(answer: &Answer) -> Option<i128> {
+    match answer {
+        Answer::Signed(value) => Some(*value),
+        Answer::Unsigned(value) => i128::try_from(*value).ok(),
+        Answer::Text(_) => None,
+    }
+}
+
+/// Whether `answer` is worth sending given what's already been `attempts`ed at `now`. Anything
+/// judged before, or on the wrong side of a too high or too low answer, is turned away, as is
+/// anything sent before the site's last wait is over.
+pub fn check(attempts: &[Attempt], answer: &Answer, now: u64) -> Result<(), Refusal> {
+    if let Some(right) = attempts.iter().find(|a| a.verdict == Verdict::Right) {
+        return Err(Refusal::Solved(right.answer.clone()));
+    }
+
+    let judged = attempts.iter().filter(|a| a.verdict != Verdict::TooSoon);
+    if let Some(attempt) = judged.clone().find(|a| &a.answer == answer) {
+        return Err(Refusal::AlreadyJudged(attempt.verdict.clone()));
+    }
+
+    if let Some(value) = number(answer) {
+        let bound = |verdict: Verdict| {
+            judged
+                .clone()
+                .filter(move |a| a.verdict == verdict)
+                .filter_map(|a| Some((number(&a.answer)?, &a.answer)))
+        };
+        if let Some((_, high)) = bound(Verdict::TooHigh)
+            .filter(|(high, _)| value >= *high)
+            .min_by_key(|(high, _)| *high)
+        {
+            return Err(Refusal::TooHigh(high.clone()));
+        }
+        if let Some((_, low)) = bound(Verdict::TooLow)
+            .filter(|(low, _)| value <= *low)
+            .max_by_key(|(low, _)| *low)
+        {
+            return Err(Refusal::TooLow(low.clone()));
+        }
+    }
+
+    if let Some(last) = attempts.last() {
+        let ready = last.at + last.wait;
+        if now < ready {
+            return Err(Refusal::Wait(ready - now));
+        }
+    }
+
+    Ok(())
+}
+
+/// Pick the verdict and any wait out of the page the site sends back.
+pub fn parse_response(page: &str) -> Result<(Verdict, u64), String> {
+    let text = match (page.find("<article>"), page.find("</article>")) {
+        (Some(start), Some(end)) if start < end => &page[start..end],
+        _ => page,
+    };
+
+    let verdict = if text.contains("That's the right answer") {
+        Verdict::Right
+    } else if text.contains("answer too recently") {
+        Verdict::TooSoon
+    } else if text.contains("your answer is too high") {
+        Verdict::TooHigh
+    } else if text.contains("your answer is too low") {
+        Verdict::TooLow
+    } else if text.contains("not the right answer") {
+        Verdict::Wrong
+    } else if text.contains("Did you already complete it") {
+        return Err(String::from(
+            "the site says this part isn't open or is already done",
+        ));
+    } else {
+        return Err(String::from("couldn't make sense of the site's response"));
+    };
+
+    Ok((verdict, wait_seconds(text)))
+}
+
+/// The wait in `You have 1m 5s left to wait` or `please wait 5 minutes before trying again`.
+fn wait_seconds(text: &str) -> u64 {
+    if let Some(end) = text.find(" left to wait") {
+        let start = text[..end].rfind("You have ").map_or(0, |start| start + 9);
+        return text[start..end]
+            .split_whitespace()
+            .map(|part| {
+                if let Some(minutes) = part.strip_suffix('m') {
+                    minutes.parse::<u64>().unwrap_or(0) * 60
+                } else {
+                    part.strip_suffix('s')
+                        .map_or(0, |seconds| seconds.parse().unwrap_or(0))
+                }
+            })
+            .sum();
+    }
+
+    if let Some(start) = text.find("wait ") {
+        let mut words = text[start + 5..].split_whitespace();
+        let count = match words.next() {
+            Some("one") => Some(1),
+            Some(count) => count.parse::<u64>().ok(),
+            None => None,
+        };
+        if let (Some(count), Some(unit)) = (count, words.next()) {
+            if unit.starts_with("minute") {
+                return count * 60;
+            } else if unit.starts_with("second") {
+                return count;
+            }
+        }
+    }
+
+    0
+}
+
+/// Sends answers for day `day` part `part`, keeping what came back next to the day's input.
+pub struct Submitter<'a> {
+    pub site: &'a Fetcher,
+    pub day: u32,
+    pub part: u32,
+}
+
+impl Submitter<'_> {
+    pub fn record_path(&self) -> PathBuf {
+        self.site
+            .cache
+            .join(format!("day{}-part{}-answers", self.day, self.part))
+    }
+
+    pub fn attempts(&self) -> Result<Vec<Attempt>, String> {
+        let path = self.record_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))
+            .and_then(|contents| parse_attempts(&contents))
+    }
+
+    fn record(&self, attempt: &Attempt) -> Result<(), String> {
+        let path = self.record_path();
+        let mut contents = if path.exists() {
+            fs::read_to_string(&path)
+                .map_err(|e| format!("failed to read {}: {e}", path.display()))?
+        } else {
+            String::new()
+        };
+        contents.push_str(&format!("{attempt}\n"));
+
+        fs::create_dir_all(&self.site.cache)
+            .map_err(|e| format!("failed to create {}: {e}", self.site.cache.display()))?;
+        fs::write(&path, contents).map_err(|e| format!("failed to write {}: {e}", path.display()))
+    }
+
+    /// Send `answer` unless `check` turns it away, and record the verdict.
+    pub fn submit(&self, answer: &Answer, now: SystemTime) -> Result<Attempt, String> {
+        let now = now
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| e.to_string())?
+            .as_secs();
+        check(&self.attempts()?, answer, now).map_err(|refusal| refusal.to_string())?;
+
+        let url = format!(
+            "{}/2023/day/{}/answer",
+            self.site.base_url.trim_end_matches('/'),
+            self.day
+        );
+        info!("submitting {answer} to {url}");
+        let page = ureq::post(&url)
+            .set("User-Agent", USER_AGENT)
+            .set("Cookie", &format!("session={}", self.site.session))
+            .send_form(&[
+                ("level", &self.part.to_string()),
+                ("answer", &answer.to_string()),
+            ])
+            .map_err(|e| match e {
+                ureq::Error::Status(code, _) => format!("{url} answered {code}"),
+                e => format!("failed to post to {url}: {e}"),
+            })?
+            .into_string()
+            .map_err(|e| format!("failed to read {url}: {e}"))?;
+
+        let (verdict, wait) = parse_response(&page)?;
+        let attempt = Attempt {
+            at: now,
+            wait,
+            verdict,
+            answer: answer.clone(),
+        };
+        self.record(&attempt)?;
+        Ok(attempt)
+    }
+}
+
+/// `submit <day> <part>`, work out the part's answer from its input and send it.
+pub fn run(args: &[String]) {
+    let (Some(day), Some(part)) = (args.first(), args.get(1)) else {
+        eprintln!("usage: submit <day> <part>");
+        process::exit(1);
+    };
+
+    if let Err(e) = submit(day, part) {
+        eprintln!("submit day {day} part {part}: {e}");
+        process::exit(1);
+    }
+}
+
+fn submit(day: &str, part: &str) -> Result<(), String> {
+    let day: u32 = day.parse().map_err(|_| format!("{day:?} is not a day"))?;
+    let part: u32 = match part.parse() {
+        Ok(part @ (1 | 2)) => part,
+        _ => return Err(format!("{part:?} is not a part, try 1 or 2")),
+    };
+    let solution = runner::solution(day).ok_or_else(|| String::from("no solution for this day"))?;
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let site = Fetcher::from_env(root)?;
+    let input = read_file_to_string(&site.cache.join(format!("day{day}-input")).to_string_lossy());
+    let answer = match solution.run(part, &input) {
+        Some(Ok(answer)) => answer,
+        Some(Err(unsolved)) => return Err(unsolved.to_string()),
+        None => return Err(String::from("no such part")),
+    };
+
+    let submitter = Submitter {
+        site: &site,
+        day,
+        part,
+    };
+    let attempt = submitter.submit(&answer, SystemTime::now())?;
+    println!("{answer}: {}", attempt.verdict.name());
+    if attempt.wait > 0 {
+        println!("wait {}s before answering again", attempt.wait);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    use crate::{common::answer::Answer, fetch::Fetcher};
+
+    use super::{check, parse_attempts, parse_response, Attempt, Refusal, Submitter, Verdict};
+
+    fn attempt(at: u64, verdict: Verdict, answer: u64) -> Attempt {
+        Attempt {
+            at,
+            wait: 60,
+            verdict,
+            answer: Answer::from(answer),
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let attempts = vec![
+            attempt(100, Verdict::TooHigh, 500),
+            attempt(200, Verdict::TooLow, 100),
+            attempt(300, Verdict::Wrong, 250),
+        ];
+
+        assert_eq!(Ok(()), check(&attempts, &Answer::from(300_u64), 400));
+        assert_eq!(
+            Err(Refusal::AlreadyJudged(Verdict::Wrong)),
+            check(&attempts, &Answer::from(250_u64), 400)
+        );
+        assert_eq!(
+            Err(Refusal::TooHigh(Answer::from(500_u64))),
+            check(&attempts, &Answer::from(501_u64), 400)
+        );
+        assert_eq!(
+            Err(Refusal::TooLow(Answer::from(100_u64))),
+            check(&attempts, &Answer::from(-3_i64), 400)
+        );
+        assert_eq!(
+            Err(Refusal::Wait(10)),
+            check(&attempts, &Answer::from(300_u64), 350)
+        );
+
+        let solved = [attempt(400, Verdict::Right, 300)];
+        assert_eq!(
+            Err(Refusal::Solved(Answer::from(300_u64))),
+            check(&solved, &Answer::from(300_u64), 1000)
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let page = |text: &str| format!("<main>\n<article><p>{text}</p></article>\n</main>");
+
+        assert_eq!(
+            Ok((Verdict::Right, 0)),
+            parse_response(&page(
+                "That's the right answer! You are one gold star closer."
+            ))
+        );
+        assert_eq!(
+            Ok((Verdict::TooHigh, 60)),
+            parse_response(&page("That's not the right answer; your answer is too high. Please wait one minute before trying again."))
+        );
+        assert_eq!(
+            Ok((Verdict::Wrong, 300)),
+            parse_response(&page(
+                "That's not the right answer. Please wait 5 minutes before trying again."
+            ))
+        );
+        assert_eq!(
+            Ok((Verdict::TooSoon, 65)),
+            parse_response(&page("You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 5s left to wait."))
+        );
+        assert!(parse_response(&page("Did you already complete it?")).is_err());
+    }
+
+    #[test]
+    fn test_attempts_round_trip() {
+        let attempts = vec![
+            attempt(1701406900, Verdict::TooLow, 12),
+            Attempt {
+                at: 1701407000,
+                wait: 0,
+                verdict: Verdict::Right,
+                answer: Answer::from("AB CD"),
+            },
+        ];
+        let written: String = attempts.iter().map(|a| format!("{a}\n")).collect();
+
+        assert_eq!(Ok(attempts), parse_attempts(&written));
+        assert!(parse_attempts("1 2 maybe 3").is_err());
+    }
+
+    /// Answer each post on a local port with `text`, sending the request body back over the
+    /// channel.
+    fn stand_in(text: &'static str) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(": ") {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let page = format!("<article><p>{text}</p></article>");
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{page}",
+                    page.len()
+                )
+                .unwrap();
+                if sender.send(String::from_utf8(body).unwrap()).is_err() {
+                    return;
+                }
+            }
+        });
+
+        (url, receiver)
+    }
+
+    #[test]
+    fn test_submit_records_and_turns_away() {
+        let (base_url, posts) = stand_in("That's not the right answer; your answer is too low. Please wait one minute before trying again.");
+        let cache = env::temp_dir().join(format!("aoc23-submit-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&cache);
+        let site = Fetcher {
+            base_url,
+            session: String::from("abc123"),
+            cache,
+        };
+        let submitter = Submitter {
+            site: &site,
+            day: 6,
+            part: 2,
+        };
+        let now = UNIX_EPOCH + Duration::from_secs(1701500000);
+
+        let attempt = submitter.submit(&Answer::from(71503_u64), now).unwrap();
+        assert_eq!((Verdict::TooLow, 60), (attempt.verdict, attempt.wait));
+        assert_eq!("level=2&answer=71503", posts.recv().unwrap());
+        assert_eq!(1, submitter.attempts().unwrap().len());
+
+        let later = now + Duration::from_secs(120);
+        assert_eq!(
+            Err(String::from("this answer was already judged too-low")),
+            submitter.submit(&Answer::from(71503_u64), later)
+        );
+        assert_eq!(
+            Err(String::from("71503 was already too low")),
+            submitter.submit(&Answer::from(9_u64), later)
+        );
+        assert_eq!(
+            Err(String::from("the site asked us to wait another 30s")),
+            submitter.submit(&Answer::from(80000_u64), now + Duration::from_secs(30))
+        );
+        assert!(posts.try_recv().is_err());
+
+        fs::remove_dir_all(&site.cache).unwrap();
+    }
+}
@@ -0,0 +1,38 @@
+use aoc23_1::{
+    common::{answer::Answer, progress::Progress},
+    day5, day6, day8,
+    runner::{check_examples, example, solutions},
+};
+
+#[test]
+fn test_every_day_matches_its_examples() {
+    for solution in solutions() {
+        if let Err(failures) = check_examples(solution.day) {
+            panic!("day {}:\n{}", solution.day, failures.join("\n"));
+        }
+    }
+}
+
+#[test]
+fn test_models_are_usable_outside_the_crate() {
+    let almanac = day5::parse(&example(5, "example")).unwrap();
+    assert_eq!(vec![79, 14, 55, 13], almanac.seeds);
+    assert_eq!(Ok(Answer::Unsigned(35)), day5::part1(&almanac));
+
+    let races = day6::parse(&example(6, "example")).unwrap();
+    assert_eq!(Some((71530, 940200)), races.kerned);
+    assert_eq!(71503, day6::ways_to_win(71530, 940200));
+
+    let documents = day8::parse(&example(8, "repeat")).unwrap();
+    assert_eq!("LLR", documents.path);
+    assert_eq!(
+        Ok(Some(6)),
+        day8::steps_from_to(
+            "AAA",
+            "ZZZ",
+            &documents.path,
+            &documents.graph,
+            &mut Progress::new("example")
+        )
+    );
+}
@@ -0,0 +1,170 @@
+//! Random and mangled inputs for every day, run through its parser and both parts. Whatever the
+//! input, each part has to come back with an answer or an error rather than panicking or
+//! running forever.
+//!
+//! Each day gets `FUZZ_ITERATIONS` inputs, 200 without it, from a generator seeded with
+//! `FUZZ_SEED`. A long run is something like
+//! `FUZZ_ITERATIONS=100000 cargo test --release --test fuzz`, and a failure reports the seed and
+//! input so it can be repeated.
+
+use std::{env, fs, time::Duration};
+
+use aoc23_1::{
+    common::rng::Rng,
+    runner::{
+        example,
+        record::{measure_within, Status},
+    },
+};
+
+// the inputs are small, so anything still going after this is stuck
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// what the puzzles are written in, so random text gets some way into the parsers
+const ALPHABET: &[u8] = b"0123456789 -:;,|=().\n*SLRJF7AKQTZabcdeghnorstuw";
+
+fn setting(name: &str, default: u64) -> u64 {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Every example input for `day`.
+fn examples(day: u32) -> Vec<String> {
+    let dir = format!("{}/examples/day{day}", env!("CARGO_MANIFEST_DIR"));
+    let mut names: Vec<String> = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("failed to read {dir}: {e}"))
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter_map(|name| Some(String::from(name.strip_suffix(".txt")?)))
+        .filter(|name| name != "answers")
+        .collect();
+    names.sort();
+    names.iter().map(|name| example(day, name)).collect()
+}
+
+/// Up to 200 characters from the alphabet.
+fn random(rng: &mut Rng) -> String {
+    (0..rng.below(200))
+        .map(|_| *rng.pick(ALPHABET) as char)
+        .collect()
+}
+
+/// A few small edits to `input`: characters changed, dropped or added, numbers made long enough
+/// to overflow, and lines repeated, dropped or swapped.
+fn mutate(rng: &mut Rng, input: &str) -> String {
+    let mut lines: Vec<Vec<u8>> = input
+        .split('\n')
+        .map(|line| line.as_bytes().to_vec())
+        .collect();
+
+    for _ in 0..rng.between(1, 4) {
+        let row = rng.below(lines.len() as u64) as usize;
+        let line = &mut lines[row];
+        let at = rng.below(line.len() as u64 + 1) as usize;
+        match rng.below(7) {
+            0 if at < line.len() => line[at] = *rng.pick(ALPHABET),
+            1 if at < line.len() => {
+                line.remove(at);
+            }
+            2 => line.insert(at, *rng.pick(ALPHABET)),
+            3 => {
+                let digits = vec![*rng.pick(b"0123456789"); rng.between(1, 25) as usize];
+                line.splice(at..at, digits);
+            }
+            4 => {
+                let copy = line.clone();
+                lines.insert(row, copy);
+            }
+            5 if lines.len() > 1 => {
+                lines.remove(row);
+            }
+            6 => {
+                let other = rng.below(lines.len() as u64) as usize;
+                lines.swap(row, other);
+            }
+            _ => {}
+        }
+    }
+
+    String::from_utf8_lossy(&lines.join(&b'\n')).into_owned()
+}
+
+fn fuzz(day: u32) {
+    let iterations = setting("FUZZ_ITERATIONS", 200);
+    let seed = setting("FUZZ_SEED", 2023);
+    let mut rng = Rng::new(seed.wrapping_add(day as u64));
+    let examples = examples(day);
+
+    for iteration in 0..iterations {
+        let input = if rng.chance(80) {
+            let mut input = rng.pick(&examples).clone();
+            for _ in 0..rng.between(1, 3) {
+                input = mutate(&mut rng, &input);
+            }
+            input
+        } else {
+            random(&mut rng)
+        };
+
+        for record in measure_within(day, &input, TIMEOUT) {
+            if let Status::Panicked(error) | Status::TimedOut(error) = &record.status {
+                panic!(
+                    "day {day} part {} {} on iteration {iteration} with FUZZ_SEED={seed}: {error}\ninput:\n{input}",
+                    record.part,
+                    record.status.name()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fuzz_day1() {
+    fuzz(1);
+}
+
+#[test]
+fn test_fuzz_day2() {
+    fuzz(2);
+}
+
+#[test]
+fn test_fuzz_day3() {
+    fuzz(3);
+}
+
+#[test]
+fn test_fuzz_day4() {
+    fuzz(4);
+}
+
+#[test]
+fn test_fuzz_day5() {
+    fuzz(5);
+}
+
+#[test]
+fn test_fuzz_day6() {
+    fuzz(6);
+}
+
+#[test]
+fn test_fuzz_day7() {
+    fuzz(7);
+}
+
+#[test]
+fn test_fuzz_day8() {
+    fuzz(8);
+}
+
+#[test]
+fn test_fuzz_day9() {
+    fuzz(9);
+}
+
+#[test]
+fn test_fuzz_day10() {
+    fuzz(10);
+}